@@ -0,0 +1,228 @@
+use super::postgres_queries as queries;
+use super::postgres_statements;
+#[cfg(feature = "tls")]
+use super::postgres_tls::{self, TlsConfig};
+use super::table_name::TableName;
+use super::AsyncOpenDatabaseConnection;
+use crate::errors::{DbKind, Error, MigraResult, StdResult};
+use crate::managers::{AsyncBatchExecute, AsyncManageMigrations, AsyncManageTransaction};
+use crate::migration;
+use std::fmt;
+use std::time::Duration;
+use tokio_postgres::Client as PostgresClient;
+#[cfg(not(feature = "tls"))]
+use tokio_postgres::NoTls;
+
+/// Predefined async `Postgres` client based on `tokio-postgres`.
+///
+/// The connection is driven by a task that is spawned on the current `tokio` runtime.
+///
+/// **Note:** Requires enabling `async-postgres` feature.
+pub struct Client {
+    conn: PostgresClient,
+    migrations_table: TableName,
+}
+
+impl Client {
+    /// Open database connection with TLS settings. Usage of TLS is controlled by the `sslmode`
+    /// parameter of the connection string, see [`PostgresTlsConfig`] for details.
+    ///
+    /// **Note:** Requires enabling `tls` feature.
+    ///
+    /// [`PostgresTlsConfig`]: super::PostgresTlsConfig
+    #[cfg(feature = "tls")]
+    pub async fn with_tls(
+        connection_string: &str,
+        migrations_table_name: &str,
+        tls_config: &TlsConfig,
+    ) -> MigraResult<Self> {
+        let migrations_table = queries::migrations_table(migrations_table_name)?;
+        let (connection_string, tls) = postgres_tls::connect_params(connection_string, tls_config)?;
+        let (conn, connection) = tokio_postgres::connect(&connection_string, tls)
+            .await
+            .map_err(|err| Error::db(err.into(), DbKind::DatabaseConnection))?;
+
+        tokio::spawn(async move {
+            // The connection error is returned by the client on the next request.
+            connection.await.ok();
+        });
+
+        tracing::debug!(
+            client = "Postgres",
+            migrations_table = migrations_table_name,
+            "Connected to database"
+        );
+        Ok(Client {
+            conn,
+            migrations_table,
+        })
+    }
+
+    /// Provide access to the original database connection.
+    #[must_use]
+    pub fn conn(&self) -> &PostgresClient {
+        &self.conn
+    }
+
+    async fn table_exists(&self, table: &TableName) -> Result<bool, tokio_postgres::Error> {
+        self.conn
+            .query_one(queries::TABLE_EXISTS, &[&table.quoted('"')])
+            .await
+            .and_then(|row| row.try_get(0))
+    }
+
+    async fn migrations_table_version(&self) -> Result<u32, tokio_postgres::Error> {
+        let version_table = queries::migrations_version_table(&self.migrations_table);
+
+        if self.table_exists(&version_table).await? {
+            let stmt = queries::select_migrations_table_version(&version_table);
+            self.conn
+                .query_one(stmt.as_str(), &[])
+                .await
+                .and_then(|row| row.try_get::<_, Option<i32>>(0))
+                .map(|version| version.map_or(0, i32::unsigned_abs))
+        } else {
+            self.table_exists(&self.migrations_table)
+                .await
+                .map(u32::from)
+        }
+    }
+}
+
+impl fmt::Debug for Client {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Client")
+            .field("migrations_table", &self.migrations_table.to_string())
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncOpenDatabaseConnection for Client {
+    #[cfg(feature = "tls")]
+    async fn manual(connection_string: &str, migrations_table_name: &str) -> MigraResult<Self> {
+        Self::with_tls(
+            connection_string,
+            migrations_table_name,
+            &TlsConfig::default(),
+        )
+        .await
+    }
+
+    #[cfg(not(feature = "tls"))]
+    async fn manual(connection_string: &str, migrations_table_name: &str) -> MigraResult<Self> {
+        let migrations_table = queries::migrations_table(migrations_table_name)?;
+        let (conn, connection) = tokio_postgres::connect(connection_string, NoTls)
+            .await
+            .map_err(|err| Error::db(err.into(), DbKind::DatabaseConnection))?;
+
+        tokio::spawn(async move {
+            // The connection error is returned by the client on the next request.
+            connection.await.ok();
+        });
+
+        tracing::debug!(
+            client = "Postgres",
+            migrations_table = migrations_table_name,
+            "Connected to database"
+        );
+        Ok(Client {
+            conn,
+            migrations_table,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncBatchExecute for Client {
+    async fn batch_execute(&mut self, sql: &str) -> StdResult<()> {
+        self.conn
+            .batch_execute(sql)
+            .await
+            .map_err(|err| postgres_statements::sql_error(sql, err).into())
+    }
+}
+
+impl AsyncManageTransaction for Client {}
+
+#[async_trait::async_trait]
+impl AsyncManageMigrations for Client {
+    async fn get_migrations_table_version(&mut self) -> MigraResult<u32> {
+        self.migrations_table_version()
+            .await
+            .map_err(|err| Error::db(err.into(), DbKind::GetMigrationsTableVersion))
+    }
+
+    async fn upgrade_migrations_table(&mut self, version: u32) -> MigraResult<()> {
+        let Some(stmt) = queries::upgrade_migrations_table(&self.migrations_table, version) else {
+            return Ok(());
+        };
+
+        self.batch_execute(&stmt).await.map_err(|err| {
+            Error::db(
+                err,
+                super::utils::upgrade_migrations_table_error_kind(version),
+            )
+        })
+    }
+
+    async fn insert_migration(
+        &mut self,
+        name: &str,
+        checksum: &str,
+        duration: Duration,
+        applied_by: &str,
+    ) -> MigraResult<u64> {
+        let stmt = queries::insert_migration(&self.migrations_table);
+
+        self.conn
+            .execute(
+                stmt.as_str(),
+                &[
+                    &name,
+                    &checksum,
+                    &super::utils::duration_to_millis(duration),
+                    &applied_by,
+                ],
+            )
+            .await
+            .map_err(|err| Error::db(err.into(), DbKind::InsertMigration))
+    }
+
+    async fn delete_migration(&mut self, name: &str) -> MigraResult<u64> {
+        let stmt = queries::delete_migration(&self.migrations_table);
+
+        self.conn
+            .execute(stmt.as_str(), &[&name])
+            .await
+            .map_err(|err| Error::db(err.into(), DbKind::DeleteMigration))
+    }
+
+    async fn get_migrations_history(&mut self) -> MigraResult<Vec<migration::AppliedMigration>> {
+        let version = self.get_migrations_table_version().await?;
+        if version == 0 {
+            return Ok(Vec::new());
+        }
+        let stmt = queries::select_migrations_history(&self.migrations_table, version);
+
+        self.conn
+            .query(stmt.as_str(), &[])
+            .await
+            .and_then(|res| {
+                res.into_iter()
+                    .map(|row| {
+                        Ok(super::utils::applied_migration_from_row(
+                            row.try_get(0)?,
+                            row.try_get(1)?,
+                            row.try_get(2)?,
+                            row.try_get(3)?,
+                            row.try_get(4)?,
+                        ))
+                    })
+                    .collect()
+            })
+            .map_err(|err| Error::db(err.into(), DbKind::GetAppliedMigrations))
+    }
+}
+
+impl super::AsyncClient for Client {}
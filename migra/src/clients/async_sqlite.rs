@@ -0,0 +1,173 @@
+use super::{AsyncOpenDatabaseConnection, OpenDatabaseConnection, SqliteClient};
+use crate::errors::{MigraResult, StdResult};
+use crate::managers::{
+    AsyncBatchExecute, AsyncManageMigrations, AsyncManageTransaction, BatchExecute,
+    ManageMigrations,
+};
+use crate::migration;
+use std::io;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+/// Predefined async `Sqlite` client.
+///
+/// `Sqlite` has no async driver, so this client wraps the blocking [`SqliteClient`] and runs
+/// each operation on the blocking thread pool of the current `tokio` runtime.
+///
+/// **Note:** Requires enabling `async-sqlite` feature.
+#[derive(Debug)]
+pub struct Client {
+    inner: Arc<Mutex<SqliteClient>>,
+}
+
+impl Client {
+    /// Runs a function with the blocking client on the blocking thread pool.
+    pub async fn run<T, E, BlockingFn>(&self, blocking_fn: BlockingFn) -> Result<T, E>
+    where
+        T: Send + 'static,
+        E: From<io::Error> + Send + 'static,
+        BlockingFn: FnOnce(&mut SqliteClient) -> Result<T, E> + Send + 'static,
+    {
+        let inner = Arc::clone(&self.inner);
+
+        tokio::task::spawn_blocking(move || {
+            let mut client = inner.lock().unwrap_or_else(PoisonError::into_inner);
+            blocking_fn(&mut client)
+        })
+        .await
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncOpenDatabaseConnection for Client {
+    async fn manual(connection_string: &str, migrations_table_name: &str) -> MigraResult<Self> {
+        let connection_string = connection_string.to_owned();
+        let migrations_table_name = migrations_table_name.to_owned();
+
+        let client = tokio::task::spawn_blocking(move || {
+            SqliteClient::manual(&connection_string, &migrations_table_name)
+        })
+        .await
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))??;
+
+        Ok(Client {
+            inner: Arc::new(Mutex::new(client)),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncBatchExecute for Client {
+    async fn batch_execute(&mut self, sql: &str) -> StdResult<()> {
+        let sql = sql.to_owned();
+        self.run(move |client| client.batch_execute(&sql)).await
+    }
+}
+
+impl AsyncManageTransaction for Client {}
+
+#[async_trait::async_trait]
+impl AsyncManageMigrations for Client {
+    async fn get_migrations_table_version(&mut self) -> MigraResult<u32> {
+        self.run(ManageMigrations::get_migrations_table_version)
+            .await
+    }
+
+    async fn upgrade_migrations_table(&mut self, version: u32) -> MigraResult<()> {
+        self.run(move |client| client.upgrade_migrations_table(version))
+            .await
+    }
+
+    async fn insert_migration(
+        &mut self,
+        name: &str,
+        checksum: &str,
+        duration: Duration,
+        applied_by: &str,
+    ) -> MigraResult<u64> {
+        let name = name.to_owned();
+        let checksum = checksum.to_owned();
+        let applied_by = applied_by.to_owned();
+
+        self.run(move |client| client.insert_migration(&name, &checksum, duration, &applied_by))
+            .await
+    }
+
+    async fn delete_migration(&mut self, name: &str) -> MigraResult<u64> {
+        let name = name.to_owned();
+        self.run(move |client| client.delete_migration(&name)).await
+    }
+
+    async fn get_migrations_history(&mut self) -> MigraResult<Vec<migration::AppliedMigration>> {
+        self.run(ManageMigrations::get_migrations_history).await
+    }
+}
+
+impl super::AsyncClient for Client {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::managers::MIGRATIONS_TABLE_VERSION;
+
+    const FIRST_MIGRATION: &str = "210218232851_create_articles";
+    const SECOND_MIGRATION: &str = "210218233414_create_persons";
+
+    async fn applied_migration_names(client: &mut Client) -> MigraResult<Vec<String>> {
+        client.get_applied_migrations().await.map(|migrations| {
+            migrations
+                .iter()
+                .map(|migration| migration.name().clone())
+                .collect()
+        })
+    }
+
+    #[tokio::test]
+    async fn upgrade_and_downgrade_migrations() -> MigraResult<()> {
+        let mut client = Client::new(":memory:").await?;
+
+        client.create_migrations_table().await?;
+        assert_eq!(
+            client.get_migrations_table_version().await?,
+            MIGRATIONS_TABLE_VERSION
+        );
+
+        let mut migrations = client.get_applied_migrations().await?;
+        for (name, content) in [
+            (
+                FIRST_MIGRATION,
+                "CREATE TABLE articles (title text NOT NULL);",
+            ),
+            (
+                SECOND_MIGRATION,
+                "CREATE TABLE persons (name text NOT NULL);",
+            ),
+        ] {
+            client.begin_transaction().await?;
+            migrations
+                .should_run_upgrade_migration_async(&mut client, name, content)
+                .await?;
+            client.commit_transaction().await?;
+        }
+        assert_eq!(
+            applied_migration_names(&mut client).await?,
+            vec![SECOND_MIGRATION, FIRST_MIGRATION]
+        );
+
+        let is_rolled_back = migrations
+            .should_run_downgrade_migration_async(
+                &mut client,
+                SECOND_MIGRATION,
+                "DROP TABLE persons;",
+            )
+            .await?;
+        assert!(is_rolled_back);
+        assert_eq!(
+            applied_migration_names(&mut client).await?,
+            vec![FIRST_MIGRATION]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,263 @@
+use crate::errors::{MigraResult, StdResult};
+use crate::managers::{
+    BatchExecute, ManageLock, ManageMigrations, ManageTransaction, RenderMigrationsSql,
+};
+use crate::migration;
+use std::fmt;
+use std::time::Duration;
+
+/// Client that records SQL instead of executing it, e.g. to review the script of
+/// the migrations before applying them.
+///
+/// Applied migrations and the version of the migrations table are read from the wrapped
+/// client. All other SQL is recorded in the order in which it would be executed,
+/// including `BEGIN` and `COMMIT` and changes of the migrations table. The migrations lock
+/// is not acquired.
+///
+/// **Note:** Durations of migrations are not known without executing them, so they are
+/// recorded as `0` milliseconds.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "postgres")]
+/// # fn main() -> migra::Result<()> {
+/// use migra::clients::{DryRunClient, OpenDatabaseConnection, PostgresClient};
+/// use migra::migrator::{Migrator, Target};
+/// use std::path::PathBuf;
+///
+/// let mut client = PostgresClient::new("postgres://postgres@localhost/app")?;
+/// let migrations_dir = PathBuf::from("database/migrations");
+///
+/// let mut dry_run_client = DryRunClient::new(&mut client);
+/// Migrator::new(&mut dry_run_client, &migrations_dir).upgrade(&Target::All)?;
+///
+/// println!("{}", dry_run_client.script());
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "postgres"))]
+/// # fn main() {}
+/// ```
+pub struct Client<'a> {
+    inner: &'a mut dyn super::Client,
+    migrations_table_version: Option<u32>,
+    statements: Vec<String>,
+}
+
+impl fmt::Debug for Client<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("DryRunClient")
+            .field("migrations_table_version", &self.migrations_table_version)
+            .field("statements", &self.statements)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> Client<'a> {
+    /// Creates a client that reads the state of migrations from the specific client.
+    #[must_use]
+    pub fn new(client: &'a mut dyn super::Client) -> Self {
+        Client {
+            inner: client,
+            migrations_table_version: None,
+            statements: Vec::new(),
+        }
+    }
+
+    /// Returns recorded statements in the order in which they would be executed.
+    #[must_use]
+    pub fn statements(&self) -> &[String] {
+        &self.statements
+    }
+
+    /// Returns recorded statements as an SQL script. Each statement is terminated with `;`
+    /// and separated by an empty line.
+    #[must_use]
+    pub fn script(&self) -> String {
+        self.statements
+            .iter()
+            .map(|stmt| {
+                let stmt = stmt.trim();
+                if stmt.ends_with(';') {
+                    stmt.to_owned()
+                } else {
+                    format!("{stmt};")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+impl BatchExecute for Client<'_> {
+    fn batch_execute(&mut self, sql: &str) -> StdResult<()> {
+        self.statements.push(sql.to_owned());
+        Ok(())
+    }
+}
+
+impl ManageTransaction for Client<'_> {}
+
+impl ManageLock for Client<'_> {
+    fn acquire_lock(&mut self, _timeout: Duration) -> MigraResult<()> {
+        Ok(())
+    }
+
+    fn release_lock(&mut self) -> MigraResult<()> {
+        Ok(())
+    }
+}
+
+impl ManageMigrations for Client<'_> {
+    fn get_migrations_table_version(&mut self) -> MigraResult<u32> {
+        match self.migrations_table_version {
+            Some(version) => Ok(version),
+            None => self.inner.get_migrations_table_version(),
+        }
+    }
+
+    fn upgrade_migrations_table(&mut self, version: u32) -> MigraResult<()> {
+        if let Some(stmt) = self.inner.upgrade_migrations_table_sql(version) {
+            self.statements.push(stmt);
+            self.migrations_table_version = Some(version);
+        }
+        Ok(())
+    }
+
+    fn insert_migration(
+        &mut self,
+        name: &str,
+        checksum: &str,
+        _duration: Duration,
+        applied_by: &str,
+    ) -> MigraResult<u64> {
+        let stmt = self
+            .inner
+            .insert_migration_sql(name, checksum, Duration::default(), applied_by);
+        self.statements.push(stmt);
+        Ok(1)
+    }
+
+    fn delete_migration(&mut self, name: &str) -> MigraResult<u64> {
+        let stmt = self.inner.delete_migration_sql(name);
+        self.statements.push(stmt);
+        Ok(1)
+    }
+
+    // The migrations table may exist or be upgraded only in the recorded statements, so
+    // the history is read according to the real version of the table.
+    fn get_migrations_history(&mut self) -> MigraResult<Vec<migration::AppliedMigration>> {
+        self.inner.get_migrations_history()
+    }
+}
+
+impl RenderMigrationsSql for Client<'_> {
+    fn upgrade_migrations_table_sql(&self, version: u32) -> Option<String> {
+        self.inner.upgrade_migrations_table_sql(version)
+    }
+
+    fn insert_migration_sql(
+        &self,
+        name: &str,
+        checksum: &str,
+        duration: Duration,
+        applied_by: &str,
+    ) -> String {
+        self.inner
+            .insert_migration_sql(name, checksum, duration, applied_by)
+    }
+
+    fn delete_migration_sql(&self, name: &str) -> String {
+        self.inner.delete_migration_sql(name)
+    }
+}
+
+impl super::Client for Client<'_> {}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+    use crate::clients::{OpenDatabaseConnection, SqliteClient};
+    use crate::embedded::{EmbeddedMigration, EmbeddedMigrations};
+    use crate::migrator::{Migrator, Target};
+
+    const FIRST_MIGRATION: &str = "210218232851_create_articles";
+    const SECOND_MIGRATION: &str = "210218233414_create_persons";
+
+    const MIGRATIONS: EmbeddedMigrations = EmbeddedMigrations::new(&[
+        EmbeddedMigration::new(
+            FIRST_MIGRATION,
+            "CREATE TABLE articles (title text NOT NULL);",
+            "DROP TABLE articles;",
+        ),
+        EmbeddedMigration::new(
+            SECOND_MIGRATION,
+            "CREATE TABLE persons (name text NOT NULL)",
+            "DROP TABLE persons",
+        ),
+    ]);
+
+    #[test]
+    fn record_upgrade_without_migrations_table() -> MigraResult<()> {
+        let mut client = SqliteClient::new(":memory:")?;
+        let mut dry_run_client = Client::new(&mut client);
+
+        let applied = Migrator::new(&mut dry_run_client, &MIGRATIONS).upgrade(&Target::All)?;
+        assert_eq!(applied, MIGRATIONS.list());
+
+        let statements = dry_run_client.statements().to_vec();
+        assert_eq!(statements.len(), 10);
+        for (version, stmt) in (1..=4).zip(&statements) {
+            assert_eq!(
+                Some(stmt),
+                client.upgrade_migrations_table_sql(version).as_ref()
+            );
+        }
+        assert_eq!(
+            &statements[4..],
+            [
+                String::from("BEGIN"),
+                String::from("CREATE TABLE articles (title text NOT NULL);"),
+                client.insert_migration_sql(
+                    FIRST_MIGRATION,
+                    &migration::calculate_checksum("CREATE TABLE articles (title text NOT NULL);"),
+                    Duration::default(),
+                    &migration::applied_by()
+                ),
+                String::from("CREATE TABLE persons (name text NOT NULL)"),
+                client.insert_migration_sql(
+                    SECOND_MIGRATION,
+                    &migration::calculate_checksum("CREATE TABLE persons (name text NOT NULL)"),
+                    Duration::default(),
+                    &migration::applied_by()
+                ),
+                String::from("COMMIT"),
+            ]
+        );
+
+        assert_eq!(client.get_migrations_table_version()?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn record_downgrade_of_applied_migrations() -> MigraResult<()> {
+        let mut client = SqliteClient::new(":memory:")?;
+        Migrator::new(&mut client, &MIGRATIONS).upgrade(&Target::All)?;
+
+        let mut dry_run_client = Client::new(&mut client);
+        Migrator::new(&mut dry_run_client, &MIGRATIONS).downgrade(&Target::Number(1))?;
+        let script = dry_run_client.script();
+
+        assert_eq!(
+            script,
+            format!(
+                "BEGIN;\n\nDROP TABLE persons;\n\n{};\n\nCOMMIT;",
+                client.delete_migration_sql(SECOND_MIGRATION)
+            )
+        );
+        assert_eq!(client.get_applied_migrations()?.len(), 2);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,397 @@
+use crate::errors::{DbKind, Error, MigraResult, StdResult};
+use crate::managers::{
+    BatchExecute, ManageLock, ManageMigrations, ManageTransaction, RenderMigrationsSql,
+    MIGRATIONS_TABLE_VERSION,
+};
+use crate::migration;
+use std::time::{Duration, SystemTime};
+
+/// In-memory client to test code that manages migrations without a database.
+///
+/// Applied migrations and the version of the migrations table are kept in memory. Executed
+/// SQL is recorded, including `BEGIN`, `COMMIT`, `ROLLBACK` and changes of the migrations
+/// table. Changes of applied migrations after `BEGIN` are discarded on `ROLLBACK`.
+///
+/// Failures can be injected at chosen statements to test error handling, e.g. rollback of
+/// the transaction.
+///
+/// **Note:** Requires enabling `testing` feature.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "testing")]
+/// # fn main() -> migra::Result<()> {
+/// use migra::clients::MockClient;
+/// use migra::managers::{ManageMigrations, ManageTransaction};
+///
+/// let mut client = MockClient::new().fail_on("CREATE TABLE persons");
+/// client.create_migrations_table()?;
+///
+/// let res = client
+///     .begin_transaction()
+///     .and_then(|()| client.run_upgrade_migration("1_articles", "CREATE TABLE articles ();"))
+///     .and_then(|()| client.run_upgrade_migration("2_persons", "CREATE TABLE persons ();"))
+///     .and_then(|()| client.commit_transaction())
+///     .or_else(|err| client.rollback_transaction().and(Err(err)));
+///
+/// assert!(res.is_err());
+/// assert_eq!(client.statements().last().map(String::as_str), Some("ROLLBACK"));
+/// assert!(client.get_applied_migrations()?.is_empty());
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "testing"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Client {
+    state: State,
+    transaction_state: Option<State>,
+    locked: bool,
+    statements: Vec<String>,
+    executed_count: usize,
+    failing_statements: Vec<usize>,
+    failing_sql: Vec<String>,
+}
+
+/// State of the migrations table.
+#[derive(Debug, Clone, Default)]
+struct State {
+    migrations_table_version: u32,
+    history: Vec<migration::AppliedMigration>,
+}
+
+impl Client {
+    /// Creates a client without the migrations table.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates the latest migrations table with the specific migrations, which are listed in
+    /// the order in which they were applied.
+    #[must_use]
+    pub fn with_applied_migrations(mut self, migrations: &migration::List) -> Self {
+        self.state = State {
+            migrations_table_version: MIGRATIONS_TABLE_VERSION,
+            history: migrations
+                .iter()
+                .rev()
+                .map(|migration| {
+                    migration::AppliedMigration::new(migration.clone(), None, None, None)
+                })
+                .collect(),
+        };
+        self
+    }
+
+    /// Fails the statement with the specific zero-based index in the order of execution,
+    /// counting failed statements too.
+    #[must_use]
+    pub fn fail_at(mut self, index: usize) -> Self {
+        self.failing_statements.push(index);
+        self
+    }
+
+    /// Fails all statements that contain the specific SQL.
+    #[must_use]
+    pub fn fail_on<S: Into<String>>(mut self, sql: S) -> Self {
+        self.failing_sql.push(sql.into());
+        self
+    }
+
+    /// Returns successfully executed statements in the order of execution.
+    #[must_use]
+    pub fn statements(&self) -> &[String] {
+        &self.statements
+    }
+
+    fn ensure_migrations_table(&self, kind: DbKind) -> MigraResult<()> {
+        if self.state.migrations_table_version == 0 {
+            return Err(Error::db("Migrations table doesn't exist".into(), kind));
+        }
+        Ok(())
+    }
+}
+
+impl BatchExecute for Client {
+    fn batch_execute(&mut self, sql: &str) -> StdResult<()> {
+        let index = self.executed_count;
+        self.executed_count += 1;
+
+        if self.failing_statements.contains(&index)
+            || self.failing_sql.iter().any(|failing| sql.contains(failing))
+        {
+            return Err(format!("Injected failure of statement {index}").into());
+        }
+
+        self.statements.push(sql.to_owned());
+        Ok(())
+    }
+}
+
+impl ManageTransaction for Client {
+    fn begin_transaction(&mut self) -> MigraResult<()> {
+        self.batch_execute("BEGIN")
+            .map_err(|err| Error::db(err, DbKind::OpenTransaction))?;
+        self.transaction_state = Some(self.state.clone());
+        Ok(())
+    }
+
+    fn rollback_transaction(&mut self) -> MigraResult<()> {
+        self.batch_execute("ROLLBACK")
+            .map_err(|err| Error::db(err, DbKind::RollbackTransaction))?;
+        if let Some(state) = self.transaction_state.take() {
+            self.state = state;
+        }
+        Ok(())
+    }
+
+    fn commit_transaction(&mut self) -> MigraResult<()> {
+        self.batch_execute("COMMIT")
+            .map_err(|err| Error::db(err, DbKind::CommitTransaction))?;
+        self.transaction_state = None;
+        Ok(())
+    }
+}
+
+impl ManageLock for Client {
+    fn acquire_lock(&mut self, _timeout: Duration) -> MigraResult<()> {
+        if self.locked {
+            return Err(Error::db(
+                "Migrations lock is already acquired".into(),
+                DbKind::AcquireLock,
+            ));
+        }
+        self.locked = true;
+        Ok(())
+    }
+
+    fn release_lock(&mut self) -> MigraResult<()> {
+        self.locked = false;
+        Ok(())
+    }
+}
+
+impl ManageMigrations for Client {
+    fn get_migrations_table_version(&mut self) -> MigraResult<u32> {
+        Ok(self.state.migrations_table_version)
+    }
+
+    fn upgrade_migrations_table(&mut self, version: u32) -> MigraResult<()> {
+        let Some(stmt) = self.upgrade_migrations_table_sql(version) else {
+            return Ok(());
+        };
+
+        self.batch_execute(&stmt).map_err(|err| {
+            let kind = if version == 1 {
+                DbKind::CreateMigrationsTable
+            } else {
+                DbKind::UpgradeMigrationsTable
+            };
+            Error::db(err, kind)
+        })?;
+        self.state.migrations_table_version = version;
+        Ok(())
+    }
+
+    fn insert_migration(
+        &mut self,
+        name: &str,
+        checksum: &str,
+        duration: Duration,
+        applied_by: &str,
+    ) -> MigraResult<u64> {
+        self.ensure_migrations_table(DbKind::InsertMigration)?;
+
+        let stmt = self.insert_migration_sql(name, checksum, duration, applied_by);
+        self.batch_execute(&stmt)
+            .map_err(|err| Error::db(err, DbKind::InsertMigration))?;
+
+        self.state.history.insert(
+            0,
+            migration::AppliedMigration::new(
+                migration::Migration::with_checksum(name, checksum),
+                Some(SystemTime::now()),
+                Some(duration),
+                Some(applied_by.to_owned()),
+            ),
+        );
+        Ok(1)
+    }
+
+    fn delete_migration(&mut self, name: &str) -> MigraResult<u64> {
+        self.ensure_migrations_table(DbKind::DeleteMigration)?;
+
+        let stmt = self.delete_migration_sql(name);
+        self.batch_execute(&stmt)
+            .map_err(|err| Error::db(err, DbKind::DeleteMigration))?;
+
+        let count = self.state.history.len();
+        self.state
+            .history
+            .retain(|applied| applied.migration().name() != name);
+        Ok((count - self.state.history.len()) as u64)
+    }
+
+    fn get_migrations_history(&mut self) -> MigraResult<Vec<migration::AppliedMigration>> {
+        Ok(self.state.history.clone())
+    }
+}
+
+impl RenderMigrationsSql for Client {
+    fn upgrade_migrations_table_sql(&self, version: u32) -> Option<String> {
+        (1..=MIGRATIONS_TABLE_VERSION)
+            .contains(&version)
+            .then(|| format!("-- Upgrade migrations table to version {version}"))
+    }
+
+    fn insert_migration_sql(
+        &self,
+        name: &str,
+        checksum: &str,
+        duration: Duration,
+        applied_by: &str,
+    ) -> String {
+        format!(
+            "INSERT INTO migrations (name, checksum, duration_ms, applied_by) VALUES ({}, {}, {}, {})",
+            quote_literal(name),
+            quote_literal(checksum),
+            duration.as_millis(),
+            quote_literal(applied_by)
+        )
+    }
+
+    fn delete_migration_sql(&self, name: &str) -> String {
+        format!(
+            "DELETE FROM migrations WHERE name = {}",
+            quote_literal(name)
+        )
+    }
+}
+
+impl super::Client for Client {}
+
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedded::{EmbeddedMigration, EmbeddedMigrations};
+    use crate::managers::TransactionStrategy;
+    use crate::migrator::{Migrator, Target};
+
+    const FIRST_MIGRATION: &str = "210218232851_create_articles";
+    const SECOND_MIGRATION: &str = "210218233414_create_persons";
+
+    const MIGRATIONS: EmbeddedMigrations = EmbeddedMigrations::new(&[
+        EmbeddedMigration::new(
+            FIRST_MIGRATION,
+            "CREATE TABLE articles (title text NOT NULL);",
+            "DROP TABLE articles;",
+        ),
+        EmbeddedMigration::new(
+            SECOND_MIGRATION,
+            "CREATE TABLE persons (name text NOT NULL);",
+            "DROP TABLE persons;",
+        ),
+    ]);
+
+    fn applied_migration_names(client: &mut Client) -> MigraResult<Vec<String>> {
+        client.get_applied_migrations().map(|migrations| {
+            migrations
+                .iter()
+                .map(|migration| migration.name().clone())
+                .collect()
+        })
+    }
+
+    #[test]
+    fn upgrade_and_downgrade_migrations() -> MigraResult<()> {
+        let mut client = Client::new();
+
+        Migrator::new(&mut client, &MIGRATIONS).upgrade(&Target::All)?;
+        assert_eq!(
+            applied_migration_names(&mut client)?,
+            vec![SECOND_MIGRATION, FIRST_MIGRATION]
+        );
+        assert_eq!(
+            client.get_migrations_table_version()?,
+            MIGRATIONS_TABLE_VERSION
+        );
+
+        Migrator::new(&mut client, &MIGRATIONS).downgrade(&Target::Number(1))?;
+        assert_eq!(applied_migration_names(&mut client)?, vec![FIRST_MIGRATION]);
+        assert_eq!(
+            &client.statements()[client.statements().len() - 4..],
+            [
+                "BEGIN",
+                "DROP TABLE persons;",
+                &format!("DELETE FROM migrations WHERE name = '{SECOND_MIGRATION}'"),
+                "COMMIT",
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn rollback_transaction_with_failed_statement() -> MigraResult<()> {
+        let mut client = Client::new().fail_on("CREATE TABLE persons");
+
+        let res = Migrator::new(&mut client, &MIGRATIONS).upgrade(&Target::All);
+        assert!(matches!(res, Err(Error::Db(ref err)) if matches!(err.kind(), DbKind::ApplySql)));
+
+        assert_eq!(applied_migration_names(&mut client)?, Vec::<String>::new());
+
+        let statements = &client.statements()[MIGRATIONS_TABLE_VERSION as usize..];
+        assert_eq!(statements.len(), 4);
+        assert_eq!(statements[0], "BEGIN");
+        assert_eq!(
+            statements[1],
+            "CREATE TABLE articles (title text NOT NULL);"
+        );
+        assert!(statements[2].starts_with(&format!(
+            "INSERT INTO migrations (name, checksum, duration_ms, applied_by) VALUES ('{FIRST_MIGRATION}'"
+        )));
+        assert_eq!(statements[3], "ROLLBACK");
+
+        Ok(())
+    }
+
+    #[test]
+    fn keep_committed_migrations_before_failed_statement() -> MigraResult<()> {
+        // Deleting the first migration from the migrations table fails after the second
+        // migration was rolled back in its own transaction.
+        let mut client = Client::new()
+            .with_applied_migrations(&MIGRATIONS.list())
+            .fail_at(6);
+
+        let res = Migrator::new(&mut client, &MIGRATIONS)
+            .transaction_strategy(TransactionStrategy::PerMigration)
+            .downgrade(&Target::All);
+        assert!(matches!(
+            res,
+            Err(Error::PartialRun(ref err))
+                if err.failed() == FIRST_MIGRATION && err.committed() == [SECOND_MIGRATION]
+        ));
+
+        assert_eq!(applied_migration_names(&mut client)?, vec![FIRST_MIGRATION]);
+        assert_eq!(
+            client.statements(),
+            [
+                "BEGIN",
+                "DROP TABLE persons;",
+                &format!("DELETE FROM migrations WHERE name = '{SECOND_MIGRATION}'"),
+                "COMMIT",
+                "BEGIN",
+                "DROP TABLE articles;",
+                "ROLLBACK",
+            ]
+        );
+
+        Ok(())
+    }
+}
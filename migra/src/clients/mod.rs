@@ -1,5 +1,7 @@
 use crate::errors::MigraResult;
-use crate::managers::{ManageMigrations, ManageTransaction};
+#[cfg(feature = "async")]
+use crate::managers::{AsyncManageMigrations, AsyncManageTransaction};
+use crate::managers::{ManageLock, ManageMigrations, ManageTransaction, RenderMigrationsSql};
 
 /// A trait that helps to open a connection to a specific database client.
 pub trait OpenDatabaseConnection
@@ -12,22 +14,101 @@ where
     }
 
     /// Open database connection manually with additional migration table name parameter.
+    ///
+    /// The table name can be schema-qualified, e.g. `ops.schema_migrations`. Returns
+    /// [`Error::InvalidTableName`] if the table name is not a valid identifier.
+    ///
+    /// [`Error::InvalidTableName`]: crate::Error::InvalidTableName
     fn manual(connection_string: &str, migrations_table_name: &str) -> MigraResult<Self>;
 }
 
-/// All client implementations that have migration and transaction manager implementations
-/// are considered clients.
-pub trait Client: ManageMigrations + ManageTransaction {}
+/// All client implementations that have migration, transaction and lock manager
+/// implementations and can render SQL of the migrations table are considered clients.
+pub trait Client: ManageMigrations + ManageTransaction + ManageLock + RenderMigrationsSql {}
 
 /// If you have complex application mechanics that allow users to choose which
 /// database they can use, then you will most likely need this helper for that.
-pub type AnyClient = Box<(dyn Client + 'static)>;
+pub type AnyClient = Box<dyn Client + 'static>;
+
+/// Async counterpart of [`OpenDatabaseConnection`].
+///
+/// **Note:** Requires enabling `async` feature.
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+#[allow(clippy::must_use_candidate)]
+pub trait AsyncOpenDatabaseConnection
+where
+    Self: Sized,
+{
+    /// Open database connection with predefined migrations table name.
+    async fn new(connection_string: &str) -> MigraResult<Self> {
+        Self::manual(connection_string, "migrations").await
+    }
+
+    /// Open database connection manually with additional migration table name parameter.
+    ///
+    /// The table name can be schema-qualified, e.g. `ops.schema_migrations`. Returns
+    /// [`Error::InvalidTableName`] if the table name is not a valid identifier.
+    ///
+    /// [`Error::InvalidTableName`]: crate::Error::InvalidTableName
+    async fn manual(connection_string: &str, migrations_table_name: &str) -> MigraResult<Self>;
+}
+
+/// All client implementations that have async migration and transaction manager
+/// implementations are considered async clients.
+///
+/// **Note:** Requires enabling `async` feature.
+#[cfg(feature = "async")]
+pub trait AsyncClient: AsyncManageMigrations + AsyncManageTransaction {}
+
+/// Async counterpart of [`AnyClient`].
+///
+/// **Note:** Requires enabling `async` feature.
+#[cfg(feature = "async")]
+pub type AnyAsyncClient = Box<dyn AsyncClient + 'static>;
+
+mod dry_run;
+pub use self::dry_run::Client as DryRunClient;
+
+#[cfg(feature = "testing")]
+mod mock;
+#[cfg(feature = "testing")]
+pub use self::mock::Client as MockClient;
+
+#[cfg(any(
+    feature = "postgres",
+    feature = "mysql",
+    feature = "sqlite",
+    feature = "async-postgres"
+))]
+mod utils;
+
+#[cfg(any(
+    feature = "postgres",
+    feature = "mysql",
+    feature = "sqlite",
+    feature = "async-postgres"
+))]
+mod table_name;
+
+#[cfg(any(feature = "postgres", feature = "async-postgres"))]
+mod postgres_queries;
+#[cfg(any(feature = "postgres", feature = "async-postgres"))]
+mod postgres_statements;
+
+#[cfg(all(feature = "tls", any(feature = "postgres", feature = "async-postgres")))]
+mod postgres_tls;
+#[cfg(all(feature = "tls", any(feature = "postgres", feature = "async-postgres")))]
+pub use self::postgres_tls::TlsConfig as PostgresTlsConfig;
 
 #[cfg(feature = "postgres")]
 mod postgres;
 #[cfg(feature = "postgres")]
 pub use self::postgres::Client as PostgresClient;
 
+#[cfg(any(feature = "mysql", test))]
+mod mysql_statements;
+
 #[cfg(feature = "mysql")]
 mod mysql;
 #[cfg(feature = "mysql")]
@@ -37,3 +118,13 @@ pub use self::mysql::Client as MysqlClient;
 mod sqlite;
 #[cfg(feature = "sqlite")]
 pub use self::sqlite::Client as SqliteClient;
+
+#[cfg(feature = "async-postgres")]
+mod async_postgres;
+#[cfg(feature = "async-postgres")]
+pub use self::async_postgres::Client as AsyncPostgresClient;
+
+#[cfg(feature = "async-sqlite")]
+mod async_sqlite;
+#[cfg(feature = "async-sqlite")]
+pub use self::async_sqlite::Client as AsyncSqliteClient;
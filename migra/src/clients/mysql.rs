@@ -1,9 +1,14 @@
+use super::mysql_statements::split_statements;
+use super::table_name::TableName;
 use super::OpenDatabaseConnection;
-use crate::errors::{DbKind, Error, MigraResult, StdResult};
-use crate::managers::{BatchExecute, ManageMigrations, ManageTransaction};
+use crate::errors::{DbKind, Error, FailedStatement, MigraResult, SqlError, StdResult};
+use crate::managers::{
+    BatchExecute, ManageLock, ManageMigrations, ManageTransaction, RenderMigrationsSql,
+};
 use crate::migration;
 use mysql::prelude::*;
 use mysql::{Pool, PooledConn};
+use std::time::Duration;
 
 /// Predefined `MySQL` client.
 ///
@@ -11,7 +16,7 @@ use mysql::{Pool, PooledConn};
 #[derive(Debug)]
 pub struct Client {
     conn: PooledConn,
-    migrations_table_name: String,
+    migrations_table: TableName,
 }
 
 impl Client {
@@ -20,57 +25,235 @@ impl Client {
     pub fn conn(&self) -> &PooledConn {
         &self.conn
     }
+
+    /// Returns a name of the user-level lock that is unique for the migrations table.
+    ///
+    /// `MySQL` limits the length of lock names to 64 characters.
+    fn lock_name(&self) -> String {
+        format!("migra:{}", &self.migrations_table)
+            .chars()
+            .take(64)
+            .collect()
+    }
+
+    fn migrations_version_table(&self) -> TableName {
+        self.migrations_table.with_suffix("_version")
+    }
+
+    fn table_exists(&mut self, table: &TableName) -> Result<bool, mysql::Error> {
+        self.conn
+            .exec_first::<u64, _, _>(
+                r"SELECT COUNT(*) FROM information_schema.tables
+                WHERE table_schema = COALESCE(?, DATABASE()) AND table_name = ?",
+                (table.schema.as_deref(), table.name.as_str()),
+            )
+            .map(|count| count.unwrap_or_default() > 0)
+    }
+
+    fn column_exists(&mut self, table: &TableName, column: &str) -> Result<bool, mysql::Error> {
+        self.conn
+            .exec_first::<u64, _, _>(
+                r"SELECT COUNT(*) FROM information_schema.columns
+                WHERE table_schema = COALESCE(?, DATABASE()) AND table_name = ?
+                    AND column_name = ?",
+                (table.schema.as_deref(), table.name.as_str(), column),
+            )
+            .map(|count| count.unwrap_or_default() > 0)
+    }
+
+    /// Returns a statement that changes the migrations table to the version.
+    fn alter_migrations_table_sql(&self, version: u32) -> Option<String> {
+        let table_name = self.migrations_table.quoted('`');
+        let stmt = match version {
+            1 => format!(
+                r"CREATE TABLE IF NOT EXISTS {table_name} (
+                    id      int             AUTO_INCREMENT PRIMARY KEY,
+                    name    varchar(256)    NOT NULL UNIQUE
+                );"
+            ),
+            2 => format!("ALTER TABLE {table_name} ADD COLUMN checksum varchar(64);"),
+            3 => format!(
+                r"ALTER TABLE {table_name}
+                    ADD COLUMN applied_at   timestamp       NULL,
+                    ADD COLUMN duration_ms  bigint,
+                    ADD COLUMN applied_by   varchar(256);"
+            ),
+            // Only the `Sqlite` migrations table is changed in this version.
+            4 => String::new(),
+            _ => return None,
+        };
+        Some(stmt)
+    }
+
+    fn set_migrations_table_version_sql(&self, version: u32) -> String {
+        let version_table_name = self.migrations_version_table().quoted('`');
+        format!(
+            r"CREATE TABLE IF NOT EXISTS {version_table_name} (version int NOT NULL);
+            DELETE FROM {version_table_name};
+            INSERT INTO {version_table_name} (version) VALUES ({version});"
+        )
+    }
 }
 
 impl OpenDatabaseConnection for Client {
     fn manual(connection_string: &str, migrations_table_name: &str) -> MigraResult<Self> {
+        let migrations_table = TableName::parse(migrations_table_name)?;
         let conn = Pool::new_manual(1, 1, connection_string)
             .and_then(|pool| pool.get_conn())
             .map_err(|err| Error::db(err.into(), DbKind::DatabaseConnection))?;
 
+        tracing::debug!(
+            client = "MySQL",
+            migrations_table = migrations_table_name,
+            "Connected to database"
+        );
         Ok(Client {
             conn,
-            migrations_table_name: migrations_table_name.to_owned(),
+            migrations_table,
         })
     }
 }
 
+/// Statements are executed one by one, because `MySQL` doesn't support the `DELIMITER`
+/// command and commits DDL statements implicitly. The error contains the failed statement
+/// and `SQLSTATE` code.
 impl BatchExecute for Client {
     fn batch_execute(&mut self, sql: &str) -> StdResult<()> {
-        self.conn.query_drop(sql).map_err(From::from)
+        split_statements(sql)
+            .iter()
+            .enumerate()
+            .try_for_each(|(index, statement)| {
+                tracing::trace!(index, len = statement.sql.len(), "Executing statement");
+                self.conn.query_drop(statement.sql).map_err(|err| {
+                    let code = match err {
+                        mysql::Error::MySqlError(ref err) => Some(err.state.clone()),
+                        _ => None,
+                    };
+                    let mut sql_error = SqlError::new(err.into()).statement(FailedStatement::new(
+                        sql,
+                        index,
+                        statement.span(),
+                    ));
+                    if let Some(code) = code {
+                        sql_error = sql_error.code(code);
+                    }
+                    sql_error.into()
+                })
+            })
     }
 }
 
 impl ManageTransaction for Client {}
 
+impl ManageLock for Client {
+    fn acquire_lock(&mut self, timeout: Duration) -> MigraResult<()> {
+        let lock_name = self.lock_name();
+        let conn = &mut self.conn;
+
+        super::utils::wait_for_lock(timeout, || {
+            conn.exec_first::<Option<i64>, _, _>("SELECT GET_LOCK(?, 0)", (&lock_name,))
+                .map(|acquired| acquired.flatten() == Some(1))
+                .map_err(From::from)
+        })
+    }
+
+    fn release_lock(&mut self) -> MigraResult<()> {
+        let lock_name = self.lock_name();
+
+        self.conn
+            .exec_drop("SELECT RELEASE_LOCK(?)", (lock_name,))
+            .map_err(|err| Error::db(err.into(), DbKind::ReleaseLock))
+    }
+}
+
 impl ManageMigrations for Client {
-    fn create_migrations_table(&mut self) -> MigraResult<()> {
-        let stmt = format!(
-            r#"CREATE TABLE IF NOT EXISTS {} (
-                id      int             AUTO_INCREMENT PRIMARY KEY,
-                name    varchar(256)    NOT NULL UNIQUE
-            )"#,
-            &self.migrations_table_name
-        );
+    fn get_migrations_table_version(&mut self) -> MigraResult<u32> {
+        let version_table = self.migrations_version_table();
 
-        self.batch_execute(&stmt)
-            .map_err(|err| Error::db(err, DbKind::CreateMigrationsTable))
+        self.table_exists(&version_table)
+            .and_then(|version_table_exists| {
+                if version_table_exists {
+                    let stmt = format!("SELECT max(version) FROM {}", version_table.quoted('`'));
+                    self.conn
+                        .query_first::<Option<u32>, _>(stmt)
+                        .map(|version| version.flatten().unwrap_or_default())
+                } else {
+                    let migrations_table = self.migrations_table.clone();
+                    self.table_exists(&migrations_table).map(u32::from)
+                }
+            })
+            .map_err(|err| Error::db(err.into(), DbKind::GetMigrationsTableVersion))
     }
 
-    fn insert_migration(&mut self, name: &str) -> MigraResult<u64> {
+    fn upgrade_migrations_table(&mut self, version: u32) -> MigraResult<()> {
+        let Some(mut stmt) = self.alter_migrations_table_sql(version) else {
+            return Ok(());
+        };
+
+        // `MySQL` commits `ALTER TABLE` implicitly, so an interrupted upgrade may add
+        // columns without recording the version. Such columns are not added again.
+        let added_column = match version {
+            2 => Some("checksum"),
+            3 => Some("applied_at"),
+            _ => None,
+        };
+        if let Some(column) = added_column {
+            let migrations_table = self.migrations_table.clone();
+            if self
+                .column_exists(&migrations_table, column)
+                .map_err(|err| {
+                    Error::db(
+                        err.into(),
+                        super::utils::upgrade_migrations_table_error_kind(version),
+                    )
+                })?
+            {
+                stmt.clear();
+            }
+        }
+
+        stmt.push('\n');
+        stmt.push_str(&self.set_migrations_table_version_sql(version));
+        self.batch_execute(&stmt).map_err(|err| {
+            Error::db(
+                err,
+                super::utils::upgrade_migrations_table_error_kind(version),
+            )
+        })
+    }
+
+    fn insert_migration(
+        &mut self,
+        name: &str,
+        checksum: &str,
+        duration: Duration,
+        applied_by: &str,
+    ) -> MigraResult<u64> {
         let stmt = format!(
-            "INSERT INTO {} (name) VALUES (?)",
-            &self.migrations_table_name
+            r"INSERT INTO {} (name, checksum, applied_at, duration_ms, applied_by)
+            VALUES (?, ?, CURRENT_TIMESTAMP, ?, ?)",
+            self.migrations_table.quoted('`')
         );
 
         self.conn
-            .exec_first(&stmt, (name,))
+            .exec_first(
+                &stmt,
+                (
+                    name,
+                    checksum,
+                    super::utils::duration_to_millis(duration),
+                    applied_by,
+                ),
+            )
             .map(Option::unwrap_or_default)
             .map_err(|err| Error::db(err.into(), DbKind::InsertMigration))
     }
 
     fn delete_migration(&mut self, name: &str) -> MigraResult<u64> {
-        let stmt = format!("DELETE FROM {} WHERE name = ?", &self.migrations_table_name);
+        let stmt = format!(
+            "DELETE FROM {} WHERE name = ?",
+            self.migrations_table.quoted('`')
+        );
 
         self.conn
             .exec_first(&stmt, (name,))
@@ -78,17 +261,83 @@ impl ManageMigrations for Client {
             .map_err(|err| Error::db(err.into(), DbKind::DeleteMigration))
     }
 
-    fn get_applied_migrations(&mut self) -> MigraResult<migration::List> {
+    fn get_migrations_history(&mut self) -> MigraResult<Vec<migration::AppliedMigration>> {
+        let version = self.get_migrations_table_version()?;
+        if version == 0 {
+            return Ok(Vec::new());
+        }
+
+        // Legacy tables don't have columns of the later versions.
+        let checksum = if version >= 2 { "checksum" } else { "NULL" };
+        let metadata = if version >= 3 {
+            "CAST(UNIX_TIMESTAMP(applied_at) AS SIGNED), duration_ms, applied_by"
+        } else {
+            "NULL, NULL, NULL"
+        };
         let stmt = format!(
-            "SELECT name FROM {} ORDER BY id DESC",
-            &self.migrations_table_name
+            "SELECT name, {checksum}, {metadata} FROM {} ORDER BY id DESC",
+            self.migrations_table.quoted('`')
         );
 
         self.conn
-            .query::<String, _>(stmt)
-            .map(From::from)
+            .query_map(
+                stmt,
+                |(name, checksum, applied_at, duration, applied_by): (
+                    String,
+                    Option<String>,
+                    Option<i64>,
+                    Option<i64>,
+                    Option<String>,
+                )| {
+                    super::utils::applied_migration_from_row(
+                        &name,
+                        checksum.as_deref(),
+                        applied_at,
+                        duration,
+                        applied_by,
+                    )
+                },
+            )
             .map_err(|err| Error::db(err.into(), DbKind::GetAppliedMigrations))
     }
 }
 
+impl RenderMigrationsSql for Client {
+    fn upgrade_migrations_table_sql(&self, version: u32) -> Option<String> {
+        self.alter_migrations_table_sql(version)
+            .map(|stmt| format!("{stmt}\n{}", self.set_migrations_table_version_sql(version)))
+    }
+
+    fn insert_migration_sql(
+        &self,
+        name: &str,
+        checksum: &str,
+        duration: Duration,
+        applied_by: &str,
+    ) -> String {
+        format!(
+            r"INSERT INTO {} (name, checksum, applied_at, duration_ms, applied_by)
+            VALUES ({}, {}, CURRENT_TIMESTAMP, {}, {})",
+            self.migrations_table.quoted('`'),
+            quote_literal(name),
+            quote_literal(checksum),
+            super::utils::duration_to_millis(duration),
+            quote_literal(applied_by)
+        )
+    }
+
+    fn delete_migration_sql(&self, name: &str) -> String {
+        format!(
+            "DELETE FROM {} WHERE name = {}",
+            self.migrations_table.quoted('`'),
+            quote_literal(name)
+        )
+    }
+}
+
 impl super::Client for Client {}
+
+/// `MySQL` treats backslashes in string literals as escape characters by default.
+fn quote_literal(value: &str) -> String {
+    super::utils::quote_literal(&value.replace('\\', "\\\\"))
+}
@@ -0,0 +1,219 @@
+use std::ops::Range;
+
+const DELIMITER_COMMAND: &str = "delimiter";
+
+/// SQL statement with a byte position where it starts in the original SQL content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Statement<'a> {
+    pub(super) sql: &'a str,
+    pub(super) start: usize,
+}
+
+impl Statement<'_> {
+    /// Returns byte range of the statement in the original SQL content.
+    pub(super) fn span(&self) -> Range<usize> {
+        self.start..self.start + self.sql.len()
+    }
+}
+
+/// Splits SQL content into statements the same way as the `mysql` command line client.
+///
+/// Statements are separated by the delimiter, which is `;` by default and can be changed
+/// with the `DELIMITER` command at the beginning of a statement. Delimiters inside quoted
+/// strings, quoted identifiers and comments are ignored. Statements without SQL are skipped.
+pub(super) fn split_statements(sql: &str) -> Vec<Statement<'_>> {
+    let mut statements = Vec::new();
+    let mut delimiter = ";";
+    let mut start: Option<usize> = None;
+    let mut pos = 0;
+
+    while pos < sql.len() {
+        let rest = &sql[pos..];
+        let Some(ch) = rest.chars().next() else {
+            break;
+        };
+
+        let skip = if start.is_none() && is_delimiter_command(rest) {
+            let line_end = rest.find('\n').unwrap_or(rest.len());
+            let new_delimiter = rest[DELIMITER_COMMAND.len()..line_end].trim();
+            if !new_delimiter.is_empty() {
+                delimiter = new_delimiter;
+            }
+            line_end
+        } else if rest.starts_with(delimiter) {
+            if let Some(start_pos) = start.take() {
+                statements.push(Statement {
+                    sql: sql[start_pos..pos].trim_end(),
+                    start: start_pos,
+                });
+            }
+            delimiter.len()
+        } else if rest.starts_with('#') || is_dash_comment(rest) {
+            rest.find('\n').unwrap_or(rest.len())
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            // Executable comments contain SQL that should be sent to the server.
+            if comment.starts_with('!') {
+                start.get_or_insert(pos);
+            }
+            comment.find("*/").map_or(rest.len(), |end| end + 4)
+        } else if matches!(ch, '\'' | '"' | '`') {
+            start.get_or_insert(pos);
+            quoted_len(rest, ch)
+        } else {
+            if !ch.is_whitespace() {
+                start.get_or_insert(pos);
+            }
+            ch.len_utf8()
+        };
+
+        pos += skip;
+    }
+
+    if let Some(start_pos) = start {
+        statements.push(Statement {
+            sql: sql[start_pos..].trim_end(),
+            start: start_pos,
+        });
+    }
+
+    statements
+}
+
+fn is_delimiter_command(rest: &str) -> bool {
+    rest.get(..DELIMITER_COMMAND.len())
+        .is_some_and(|command| command.eq_ignore_ascii_case(DELIMITER_COMMAND))
+        && rest[DELIMITER_COMMAND.len()..]
+            .chars()
+            .next()
+            .is_some_and(char::is_whitespace)
+}
+
+/// `MySQL` requires a whitespace after `--` to start a comment.
+fn is_dash_comment(rest: &str) -> bool {
+    rest.strip_prefix("--")
+        .is_some_and(|comment| comment.chars().next().map_or(true, char::is_whitespace))
+}
+
+/// Returns the length of the quoted string or identifier including quotes. Quotes are
+/// escaped by doubling, strings also support escaping with a backslash.
+fn quoted_len(rest: &str, quote: char) -> usize {
+    let mut chars = rest.char_indices().skip(1);
+
+    while let Some((index, ch)) = chars.next() {
+        if ch == '\\' && quote != '`' {
+            chars.next();
+        } else if ch == quote {
+            return index + 1;
+        }
+    }
+
+    rest.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::{DbKind, Error, FailedStatement, SqlError};
+
+    fn statements(sql: &str) -> Vec<(&str, usize)> {
+        split_statements(sql)
+            .into_iter()
+            .map(|statement| {
+                let line = sql[..statement.start].matches('\n').count() + 1;
+                (statement.sql, line)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn split_statements_by_semicolon() {
+        assert_eq!(
+            statements("CREATE TABLE tags (name text);\n\nDROP TABLE tags;\nSELECT 1"),
+            vec![
+                ("CREATE TABLE tags (name text)", 1),
+                ("DROP TABLE tags", 3),
+                ("SELECT 1", 4)
+            ]
+        );
+    }
+
+    #[test]
+    fn ignore_delimiters_in_quotes_and_comments() {
+        let sql = r#"-- comment;
+# another comment;
+/* multiline;
+comment */
+INSERT INTO tags (name) VALUES ('a;b', "c\";d", 'e'';f');
+SELECT `weird;column` FROM tags; -- trailing comment
+"#;
+
+        assert_eq!(
+            statements(sql),
+            vec![
+                (
+                    r#"INSERT INTO tags (name) VALUES ('a;b', "c\";d", 'e'';f')"#,
+                    5
+                ),
+                ("SELECT `weird;column` FROM tags", 6)
+            ]
+        );
+    }
+
+    #[test]
+    fn keep_executable_comments() {
+        assert_eq!(
+            statements("/*!40101 SET NAMES utf8 */;\nSELECT 1;"),
+            vec![("/*!40101 SET NAMES utf8 */", 1), ("SELECT 1", 2)]
+        );
+    }
+
+    #[test]
+    fn change_delimiter() {
+        let sql = r"DROP PROCEDURE IF EXISTS add_tag;
+
+DELIMITER $$
+CREATE PROCEDURE add_tag(tag_name text)
+BEGIN
+    INSERT INTO tags (name) VALUES (tag_name);
+END$$
+delimiter ;
+
+CALL add_tag('migra');
+";
+
+        assert_eq!(
+            statements(sql),
+            vec![
+                ("DROP PROCEDURE IF EXISTS add_tag", 1),
+                (
+                    "CREATE PROCEDURE add_tag(tag_name text)\nBEGIN\n    INSERT INTO tags (name) VALUES (tag_name);\nEND",
+                    4
+                ),
+                ("CALL add_tag('migra')", 10)
+            ]
+        );
+    }
+
+    #[test]
+    fn show_line_of_failed_statement() {
+        let sql = "SELECT 1;\n\nSELECT *\nFROM unknown;";
+        let statement = &split_statements(sql)[1];
+        let error = Error::db(
+            SqlError::new("Table 'unknown' doesn't exist".into())
+                .statement(FailedStatement::new(sql, 1, statement.span()))
+                .into(),
+            DbKind::ApplySql,
+        );
+
+        assert_eq!(
+            error.to_string(),
+            "Failed to apply sql at line 3 (statement 2) - Table 'unknown' doesn't exist"
+        );
+    }
+
+    #[test]
+    fn skip_empty_statements() {
+        assert!(statements("").is_empty());
+        assert!(statements(";\n;  -- comment\n").is_empty());
+    }
+}
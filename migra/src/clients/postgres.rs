@@ -1,105 +1,250 @@
+use super::postgres_queries as queries;
+use super::postgres_statements;
+#[cfg(feature = "tls")]
+use super::postgres_tls::{self, TlsConfig};
+use super::table_name::TableName;
 use super::OpenDatabaseConnection;
 use crate::errors::{DbKind, Error, MigraResult, StdResult};
-use crate::managers::{BatchExecute, ManageMigrations, ManageTransaction};
+use crate::managers::{
+    BatchExecute, ManageLock, ManageMigrations, ManageTransaction, RenderMigrationsSql,
+};
 use crate::migration;
-use postgres::{Client as PostgresClient, NoTls};
+use postgres::Client as PostgresClient;
+#[cfg(not(feature = "tls"))]
+use postgres::NoTls;
 use std::fmt;
+use std::time::Duration;
 
 /// Predefined `Postgres` client.
 ///
 /// **Note:** Requires enabling `postgres` feature.
 pub struct Client {
     conn: PostgresClient,
-    migrations_table_name: String,
+    migrations_table: TableName,
 }
 
 impl Client {
+    /// Open database connection with TLS settings. Usage of TLS is controlled by the `sslmode`
+    /// parameter of the connection string, see [`PostgresTlsConfig`] for details.
+    ///
+    /// **Note:** Requires enabling `tls` feature.
+    ///
+    /// [`PostgresTlsConfig`]: super::PostgresTlsConfig
+    #[cfg(feature = "tls")]
+    pub fn with_tls(
+        connection_string: &str,
+        migrations_table_name: &str,
+        tls_config: &TlsConfig,
+    ) -> MigraResult<Self> {
+        let migrations_table = queries::migrations_table(migrations_table_name)?;
+        let (connection_string, tls) = postgres_tls::connect_params(connection_string, tls_config)?;
+        let conn = PostgresClient::connect(&connection_string, tls)
+            .map_err(|err| Error::db(err.into(), DbKind::DatabaseConnection))?;
+        tracing::debug!(
+            client = "Postgres",
+            migrations_table = migrations_table_name,
+            "Connected to database"
+        );
+        Ok(Client {
+            conn,
+            migrations_table,
+        })
+    }
+
     /// Provide access to the original database connection.
     #[must_use]
     pub fn conn(&self) -> &PostgresClient {
         &self.conn
     }
+
+    fn table_exists(&mut self, table: &TableName) -> Result<bool, postgres::Error> {
+        self.conn
+            .query_one(queries::TABLE_EXISTS, &[&table.quoted('"')])
+            .and_then(|row| row.try_get(0))
+    }
 }
 
 impl fmt::Debug for Client {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt.debug_struct("Client")
-            .field("migrations_table_name", &self.migrations_table_name)
-            .finish()
+            .field("migrations_table", &self.migrations_table.to_string())
+            .finish_non_exhaustive()
     }
 }
 
 impl OpenDatabaseConnection for Client {
+    #[cfg(feature = "tls")]
     fn manual(connection_string: &str, migrations_table_name: &str) -> MigraResult<Self> {
+        Self::with_tls(
+            connection_string,
+            migrations_table_name,
+            &TlsConfig::default(),
+        )
+    }
+
+    #[cfg(not(feature = "tls"))]
+    fn manual(connection_string: &str, migrations_table_name: &str) -> MigraResult<Self> {
+        let migrations_table = queries::migrations_table(migrations_table_name)?;
         let conn = PostgresClient::connect(connection_string, NoTls)
             .map_err(|err| Error::db(err.into(), DbKind::DatabaseConnection))?;
+        tracing::debug!(
+            client = "Postgres",
+            migrations_table = migrations_table_name,
+            "Connected to database"
+        );
         Ok(Client {
             conn,
-            migrations_table_name: migrations_table_name.to_owned(),
+            migrations_table,
         })
     }
 }
 
 impl BatchExecute for Client {
     fn batch_execute(&mut self, sql: &str) -> StdResult<()> {
-        self.conn.batch_execute(sql).map_err(From::from)
+        self.conn
+            .batch_execute(sql)
+            .map_err(|err| postgres_statements::sql_error(sql, err).into())
     }
 }
 
 impl ManageTransaction for Client {}
 
+impl ManageLock for Client {
+    fn acquire_lock(&mut self, timeout: Duration) -> MigraResult<()> {
+        let lock_key = queries::lock_key(&self.migrations_table);
+        let conn = &mut self.conn;
+
+        super::utils::wait_for_lock(timeout, || {
+            conn.query_one(queries::TRY_ADVISORY_LOCK, &[&lock_key])
+                .and_then(|row| row.try_get(0))
+                .map_err(From::from)
+        })
+    }
+
+    fn release_lock(&mut self) -> MigraResult<()> {
+        self.conn
+            .query_one(
+                queries::ADVISORY_UNLOCK,
+                &[&queries::lock_key(&self.migrations_table)],
+            )
+            .map(drop)
+            .map_err(|err| Error::db(err.into(), DbKind::ReleaseLock))
+    }
+}
+
 impl ManageMigrations for Client {
-    fn create_migrations_table(&mut self) -> MigraResult<()> {
-        let stmt = format!(
-            r#"CREATE TABLE IF NOT EXISTS {} (
-                id      serial      PRIMARY KEY,
-                name    text        NOT NULL UNIQUE
-            )"#,
-            &self.migrations_table_name
-        );
+    fn get_migrations_table_version(&mut self) -> MigraResult<u32> {
+        let version_table = queries::migrations_version_table(&self.migrations_table);
+
+        self.table_exists(&version_table)
+            .and_then(|version_table_exists| {
+                if version_table_exists {
+                    let stmt = queries::select_migrations_table_version(&version_table);
+                    self.conn
+                        .query_one(stmt.as_str(), &[])
+                        .and_then(|row| row.try_get::<_, Option<i32>>(0))
+                        .map(|version| version.map_or(0, i32::unsigned_abs))
+                } else {
+                    let migrations_table = self.migrations_table.clone();
+                    self.table_exists(&migrations_table).map(u32::from)
+                }
+            })
+            .map_err(|err| Error::db(err.into(), DbKind::GetMigrationsTableVersion))
+    }
+
+    fn upgrade_migrations_table(&mut self, version: u32) -> MigraResult<()> {
+        let Some(stmt) = self.upgrade_migrations_table_sql(version) else {
+            return Ok(());
+        };
 
-        self.batch_execute(&stmt)
-            .map_err(|err| Error::db(err, DbKind::CreateMigrationsTable))
+        self.batch_execute(&stmt).map_err(|err| {
+            Error::db(
+                err,
+                super::utils::upgrade_migrations_table_error_kind(version),
+            )
+        })
     }
 
-    fn insert_migration(&mut self, name: &str) -> MigraResult<u64> {
-        let stmt = format!(
-            "INSERT INTO {} (name) VALUES ($1)",
-            &self.migrations_table_name
-        );
+    fn insert_migration(
+        &mut self,
+        name: &str,
+        checksum: &str,
+        duration: Duration,
+        applied_by: &str,
+    ) -> MigraResult<u64> {
+        let stmt = queries::insert_migration(&self.migrations_table);
 
         self.conn
-            .execute(stmt.as_str(), &[&name])
+            .execute(
+                stmt.as_str(),
+                &[
+                    &name,
+                    &checksum,
+                    &super::utils::duration_to_millis(duration),
+                    &applied_by,
+                ],
+            )
             .map_err(|err| Error::db(err.into(), DbKind::InsertMigration))
     }
 
     fn delete_migration(&mut self, name: &str) -> MigraResult<u64> {
-        let stmt = format!(
-            "DELETE FROM {} WHERE name = $1",
-            &self.migrations_table_name
-        );
+        let stmt = queries::delete_migration(&self.migrations_table);
 
         self.conn
             .execute(stmt.as_str(), &[&name])
             .map_err(|err| Error::db(err.into(), DbKind::DeleteMigration))
     }
 
-    fn get_applied_migrations(&mut self) -> MigraResult<migration::List> {
-        let stmt = format!(
-            "SELECT name FROM {} ORDER BY id DESC",
-            &self.migrations_table_name
-        );
+    fn get_migrations_history(&mut self) -> MigraResult<Vec<migration::AppliedMigration>> {
+        let version = self.get_migrations_table_version()?;
+        if version == 0 {
+            return Ok(Vec::new());
+        }
+        let stmt = queries::select_migrations_history(&self.migrations_table, version);
 
         self.conn
             .query(stmt.as_str(), &[])
             .and_then(|res| {
                 res.into_iter()
-                    .map(|row| row.try_get(0))
-                    .collect::<Result<Vec<String>, _>>()
+                    .map(|row| {
+                        Ok(super::utils::applied_migration_from_row(
+                            row.try_get(0)?,
+                            row.try_get(1)?,
+                            row.try_get(2)?,
+                            row.try_get(3)?,
+                            row.try_get(4)?,
+                        ))
+                    })
+                    .collect()
             })
-            .map(From::from)
             .map_err(|err| Error::db(err.into(), DbKind::GetAppliedMigrations))
     }
 }
 
+impl RenderMigrationsSql for Client {
+    fn upgrade_migrations_table_sql(&self, version: u32) -> Option<String> {
+        queries::upgrade_migrations_table(&self.migrations_table, version)
+    }
+
+    fn insert_migration_sql(
+        &self,
+        name: &str,
+        checksum: &str,
+        duration: Duration,
+        applied_by: &str,
+    ) -> String {
+        queries::insert_migration_with_values(
+            &self.migrations_table,
+            name,
+            checksum,
+            super::utils::duration_to_millis(duration),
+            applied_by,
+        )
+    }
+
+    fn delete_migration_sql(&self, name: &str) -> String {
+        queries::delete_migration_with_values(&self.migrations_table, name)
+    }
+}
+
 impl super::Client for Client {}
@@ -0,0 +1,141 @@
+//! SQL statements of the migrations table that are shared between the `Postgres` clients.
+
+use super::table_name::{quote_identifier, TableName};
+#[cfg(feature = "postgres")]
+use super::utils::quote_literal;
+use crate::errors::MigraResult;
+use crate::migration;
+
+pub(super) const TABLE_EXISTS: &str = "SELECT to_regclass($1) IS NOT NULL";
+pub(super) const TRY_ADVISORY_LOCK: &str = "SELECT pg_try_advisory_lock($1)";
+pub(super) const ADVISORY_UNLOCK: &str = "SELECT pg_advisory_unlock($1)";
+
+/// Parses the migrations table name.
+///
+/// `Postgres` folds unquoted identifiers to lower case, so the name is lowercased to keep
+/// using migrations tables that were created before the names were quoted.
+pub(super) fn migrations_table(migrations_table_name: &str) -> MigraResult<TableName> {
+    TableName::parse(&migrations_table_name.to_lowercase())
+}
+
+/// Returns a key of the advisory lock that is unique for the migrations table.
+pub(super) fn lock_key(migrations_table: &TableName) -> i64 {
+    let checksum = migration::calculate_checksum(&migrations_table.to_string());
+    i64::from_str_radix(&checksum[..15], 16).unwrap_or_default()
+}
+
+pub(super) fn migrations_version_table(migrations_table: &TableName) -> TableName {
+    migrations_table.with_suffix("_version")
+}
+
+pub(super) fn select_migrations_table_version(version_table: &TableName) -> String {
+    format!("SELECT max(version) FROM {}", version_table.quoted('"'))
+}
+
+/// Returns SQL to upgrade the migrations table to the specific version and store the new
+/// version, or `None` if the version is unknown.
+pub(super) fn upgrade_migrations_table(
+    migrations_table: &TableName,
+    version: u32,
+) -> Option<String> {
+    let table_name = migrations_table.quoted('"');
+    let stmt = match version {
+        1 => {
+            let create_schema = match migrations_table.schema {
+                Some(ref schema) => format!(
+                    "CREATE SCHEMA IF NOT EXISTS {};",
+                    quote_identifier(schema, '"')
+                ),
+                None => String::new(),
+            };
+            format!(
+                r"{create_schema}
+                CREATE TABLE IF NOT EXISTS {table_name} (
+                    id      serial      PRIMARY KEY,
+                    name    text        NOT NULL UNIQUE
+                );"
+            )
+        }
+        2 => format!("ALTER TABLE {table_name} ADD COLUMN checksum text;"),
+        3 => format!(
+            r"ALTER TABLE {table_name}
+                ADD COLUMN applied_at   timestamptz,
+                ADD COLUMN duration_ms  bigint,
+                ADD COLUMN applied_by   text;"
+        ),
+        // Only the `Sqlite` migrations table is changed in this version.
+        4 => String::new(),
+        _ => return None,
+    };
+
+    let version_table_name = migrations_version_table(migrations_table).quoted('"');
+    Some(format!(
+        r"{stmt}
+        CREATE TABLE IF NOT EXISTS {version_table_name} (version integer NOT NULL);
+        DELETE FROM {version_table_name};
+        INSERT INTO {version_table_name} (version) VALUES ({version});"
+    ))
+}
+
+pub(super) fn insert_migration(migrations_table: &TableName) -> String {
+    format!(
+        r"INSERT INTO {} (name, checksum, applied_at, duration_ms, applied_by)
+        VALUES ($1, $2, CURRENT_TIMESTAMP, $3, $4)",
+        migrations_table.quoted('"')
+    )
+}
+
+/// Returns [`insert_migration`] statement with inlined values.
+#[cfg(feature = "postgres")]
+pub(super) fn insert_migration_with_values(
+    migrations_table: &TableName,
+    name: &str,
+    checksum: &str,
+    duration_ms: i64,
+    applied_by: &str,
+) -> String {
+    format!(
+        r"INSERT INTO {} (name, checksum, applied_at, duration_ms, applied_by)
+        VALUES ({}, {}, CURRENT_TIMESTAMP, {duration_ms}, {})",
+        migrations_table.quoted('"'),
+        quote_literal(name),
+        quote_literal(checksum),
+        quote_literal(applied_by)
+    )
+}
+
+pub(super) fn delete_migration(migrations_table: &TableName) -> String {
+    format!(
+        "DELETE FROM {} WHERE name = $1",
+        migrations_table.quoted('"')
+    )
+}
+
+/// Returns [`delete_migration`] statement with inlined values.
+#[cfg(feature = "postgres")]
+pub(super) fn delete_migration_with_values(migrations_table: &TableName, name: &str) -> String {
+    format!(
+        "DELETE FROM {} WHERE name = {}",
+        migrations_table.quoted('"'),
+        quote_literal(name)
+    )
+}
+
+/// Returns SQL to select the migrations history from the migrations table of the specific
+/// version. Legacy tables don't have columns of the later versions, so they are `NULL`.
+pub(super) fn select_migrations_history(migrations_table: &TableName, version: u32) -> String {
+    let checksum = if version >= 2 {
+        "checksum"
+    } else {
+        "NULL::text"
+    };
+    let metadata = if version >= 3 {
+        "CAST(EXTRACT(EPOCH FROM applied_at) AS bigint), duration_ms, applied_by"
+    } else {
+        "NULL::bigint, NULL::bigint, NULL::text"
+    };
+    format!(
+        "SELECT name, {checksum}, {metadata} FROM {} ORDER BY id DESC",
+        migrations_table.quoted('"')
+    )
+}
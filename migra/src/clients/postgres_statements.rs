@@ -0,0 +1,188 @@
+use crate::errors::{FailedStatement, SqlError};
+#[cfg(feature = "postgres")]
+use postgres::{error::ErrorPosition, Error as PostgresError};
+use std::ops::Range;
+#[cfg(not(feature = "postgres"))]
+use tokio_postgres::{error::ErrorPosition, Error as PostgresError};
+
+/// Converts the error of the executed SQL to [`SqlError`] with `SQLSTATE`, detail, hint and
+/// the failed statement if `Postgres` reports the error position.
+///
+/// The error message of the database is used as an origin error, because the driver error
+/// only says "db error".
+pub(super) fn sql_error(sql: &str, error: PostgresError) -> SqlError {
+    let Some(db_error) = error.as_db_error() else {
+        return SqlError::new(error.into());
+    };
+
+    let message = format!("{}: {}", db_error.severity(), db_error.message());
+    let mut sql_error = SqlError::new(message.into()).code(db_error.code().code());
+    if let Some(detail) = db_error.detail() {
+        sql_error = sql_error.detail(detail);
+    }
+    if let Some(hint) = db_error.hint() {
+        sql_error = sql_error.hint(hint);
+    }
+    if let Some(&ErrorPosition::Original(position)) = db_error.position() {
+        if let Some(statement) = failed_statement(sql, position as usize) {
+            sql_error = sql_error.statement(statement);
+        }
+    }
+    sql_error
+}
+
+/// Finds the statement by the error position, that is a one-based index of a character in SQL.
+fn failed_statement(sql: &str, position: usize) -> Option<FailedStatement> {
+    let error_pos = sql
+        .char_indices()
+        .nth(position.checked_sub(1)?)
+        .map(|(pos, _)| pos)?;
+
+    statement_spans(sql)
+        .into_iter()
+        .enumerate()
+        .take_while(|(_, span)| span.start <= error_pos)
+        .last()
+        .map(|(index, span)| FailedStatement::new(sql, index, span).with_error_position(error_pos))
+}
+
+/// Returns byte ranges of statements in SQL. Statements are separated by `;`, which is ignored
+/// inside quoted strings and identifiers, dollar-quoted strings and comments. Leading comments
+/// aren't included into statements.
+fn statement_spans(sql: &str) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut pos = 0;
+
+    while pos < sql.len() {
+        let rest = &sql[pos..];
+        let Some(ch) = rest.chars().next() else {
+            break;
+        };
+
+        let skip = if ch == ';' {
+            if let Some(start_pos) = start.take() {
+                spans.push(start_pos..start_pos + sql[start_pos..pos].trim_end().len());
+            }
+            1
+        } else if rest.starts_with("--") {
+            rest.find('\n').unwrap_or(rest.len())
+        } else if rest.starts_with("/*") {
+            block_comment_len(rest)
+        } else if matches!(ch, '\'' | '"') {
+            start.get_or_insert(pos);
+            rest[1..].find(ch).map_or(rest.len(), |end| end + 2)
+        } else if let Some(tag) = dollar_quote_tag(rest).filter(|_| !follows_identifier(sql, pos)) {
+            start.get_or_insert(pos);
+            rest[tag.len()..]
+                .find(tag)
+                .map_or(rest.len(), |end| end + tag.len() * 2)
+        } else {
+            if !ch.is_whitespace() {
+                start.get_or_insert(pos);
+            }
+            ch.len_utf8()
+        };
+
+        pos += skip;
+    }
+
+    if let Some(start_pos) = start {
+        spans.push(start_pos..start_pos + sql[start_pos..].trim_end().len());
+    }
+
+    spans
+}
+
+/// Returns the length of the block comment, which can be nested in `Postgres`.
+fn block_comment_len(rest: &str) -> usize {
+    let mut depth = 0;
+    let mut pos = 0;
+
+    while pos < rest.len() {
+        if rest[pos..].starts_with("/*") {
+            depth += 1;
+            pos += 2;
+        } else if rest[pos..].starts_with("*/") {
+            depth -= 1;
+            pos += 2;
+            if depth == 0 {
+                return pos;
+            }
+        } else {
+            pos += rest[pos..].chars().next().map_or(1, char::len_utf8);
+        }
+    }
+
+    rest.len()
+}
+
+/// Returns the opening tag of the dollar-quoted string, e.g. `$$` or `$body$`.
+fn dollar_quote_tag(rest: &str) -> Option<&str> {
+    let tag = rest.strip_prefix('$')?;
+    let end = tag.find('$')?;
+    let name = &tag[..end];
+
+    let is_valid_name = name.chars().next().map_or(true, |ch| !ch.is_ascii_digit())
+        && name.chars().all(|ch| ch.is_alphanumeric() || ch == '_');
+    is_valid_name.then(|| &rest[..end + 2])
+}
+
+/// Identifiers can contain `$`, that doesn't start a dollar-quoted string.
+fn follows_identifier(sql: &str, pos: usize) -> bool {
+    sql[..pos]
+        .chars()
+        .next_back()
+        .is_some_and(|ch| ch.is_alphanumeric() || ch == '_' || ch == '$')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statements(sql: &str) -> Vec<&str> {
+        statement_spans(sql)
+            .into_iter()
+            .map(|span| &sql[span])
+            .collect()
+    }
+
+    #[test]
+    fn split_statements_by_semicolon() {
+        let sql = r#"-- comment;
+CREATE TABLE tags (name text DEFAULT 'a;b', "c;d" text);
+/* block /* nested; */ comment; */
+CREATE FUNCTION add_tag(tag_name text) RETURNS void AS $body$
+    INSERT INTO tags (name) VALUES (tag_name);
+$body$ LANGUAGE sql;
+SELECT $$a;b$$, price$1 FROM tags
+"#;
+
+        assert_eq!(
+            statements(sql),
+            vec![
+                r#"CREATE TABLE tags (name text DEFAULT 'a;b', "c;d" text)"#,
+                "CREATE FUNCTION add_tag(tag_name text) RETURNS void AS $body$\n    INSERT INTO tags (name) VALUES (tag_name);\n$body$ LANGUAGE sql",
+                "SELECT $$a;b$$, price$1 FROM tags",
+            ]
+        );
+    }
+
+    #[test]
+    fn find_failed_statement_by_position() {
+        let sql = "SELECT 1;\n\n/* ↓↓↓ */\nSELECT *\nFROM unknown;\nSELECT 2;";
+        let position = sql.chars().take_while(|&ch| ch != 'u').count() + 1;
+
+        let statement = failed_statement(sql, position).unwrap();
+        assert_eq!(statement.index(), 1);
+        assert_eq!(statement.line(), 4);
+        assert_eq!(statement.sql(), "SELECT *\nFROM unknown");
+        assert_eq!(
+            statement.error_position().map(|pos| &sql[pos..pos + 7]),
+            Some("unknown")
+        );
+
+        assert_eq!(failed_statement(sql, 0), None);
+        assert_eq!(failed_statement(sql, sql.len() + 1), None);
+    }
+}
@@ -0,0 +1,236 @@
+use crate::errors::{DbKind, Error, MigraResult, StdResult};
+use native_tls::{Certificate, Identity, TlsConnector};
+use postgres_native_tls::MakeTlsConnector;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SSL_MODE_PARAM_PREFIX: &str = "sslmode=";
+
+/// TLS settings of `Postgres` clients.
+///
+/// Usage of TLS is controlled by the `sslmode` parameter of the connection string
+/// in the same way as `libpq` does:
+///
+/// * `disable` - TLS is not used.
+/// * `allow`, `prefer` - TLS is used if the server supports it. It is the default mode.
+/// * `require` - TLS is required. The server certificate is verified only if the root
+///   certificate is set.
+/// * `verify-ca` - TLS is required and the server certificate is verified.
+/// * `verify-full` - TLS is required, the server certificate and its host name are verified.
+///
+/// **Note:** Requires enabling `tls` feature.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TlsConfig {
+    root_cert: Option<PathBuf>,
+    client_cert: Option<(PathBuf, PathBuf)>,
+}
+
+impl TlsConfig {
+    /// Creates TLS settings that trust system root certificates and don't send
+    /// a client certificate.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a path to the PEM-encoded root certificate, which is used instead of system root
+    /// certificates to verify the server certificate.
+    #[must_use]
+    pub fn root_cert<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.root_cert = Some(path.into());
+        self
+    }
+
+    /// Sets paths to the PEM-encoded client certificate and its PKCS #8 private key, which are
+    /// sent to the server for authentication.
+    #[must_use]
+    pub fn client_cert<C: Into<PathBuf>, K: Into<PathBuf>>(
+        mut self,
+        cert_path: C,
+        key_path: K,
+    ) -> Self {
+        self.client_cert = Some((cert_path.into(), key_path.into()));
+        self
+    }
+}
+
+/// `sslmode` values that are supported by `libpq`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SslMode {
+    Disable,
+    Allow,
+    Prefer,
+    Require,
+    VerifyCa,
+    VerifyFull,
+}
+
+impl SslMode {
+    fn parse(value: &str) -> MigraResult<Self> {
+        match value {
+            "disable" => Ok(SslMode::Disable),
+            "allow" => Ok(SslMode::Allow),
+            "prefer" => Ok(SslMode::Prefer),
+            "require" => Ok(SslMode::Require),
+            "verify-ca" => Ok(SslMode::VerifyCa),
+            "verify-full" => Ok(SslMode::VerifyFull),
+            _ => Err(Error::db(
+                format!(r#"invalid value "{value}" for option "sslmode""#).into(),
+                DbKind::DatabaseConnection,
+            )),
+        }
+    }
+
+    /// `tokio-postgres` supports only `disable`, `prefer` and `require` modes, and leaves
+    /// certificate verification to the TLS connector.
+    fn connection_value(self) -> &'static str {
+        match self {
+            SslMode::Disable => "disable",
+            SslMode::Allow | SslMode::Prefer => "prefer",
+            SslMode::Require | SslMode::VerifyCa | SslMode::VerifyFull => "require",
+        }
+    }
+}
+
+/// Returns the connection string that is supported by `tokio-postgres` and the TLS connector
+/// that verifies the server certificate according to `sslmode`.
+pub(super) fn connect_params(
+    connection_string: &str,
+    tls_config: &TlsConfig,
+) -> MigraResult<(String, MakeTlsConnector)> {
+    let (connection_string, ssl_mode) = replace_ssl_mode(connection_string)?;
+    let connector = make_connector(ssl_mode, tls_config)
+        .map_err(|err| Error::db(err, DbKind::DatabaseConnection))?;
+    Ok((connection_string, connector))
+}
+
+fn make_connector(ssl_mode: SslMode, tls_config: &TlsConfig) -> StdResult<MakeTlsConnector> {
+    let mut builder = TlsConnector::builder();
+
+    if let Some(ref path) = tls_config.root_cert {
+        builder
+            .add_root_certificate(Certificate::from_pem(&read_pem(path)?)?)
+            .disable_built_in_roots(true);
+    }
+
+    if let Some((ref cert_path, ref key_path)) = tls_config.client_cert {
+        builder.identity(Identity::from_pkcs8(
+            &read_pem(cert_path)?,
+            &read_pem(key_path)?,
+        )?);
+    }
+
+    let verify_cert = match ssl_mode {
+        SslMode::Disable | SslMode::Allow | SslMode::Prefer => false,
+        SslMode::Require => tls_config.root_cert.is_some(),
+        SslMode::VerifyCa | SslMode::VerifyFull => true,
+    };
+
+    builder
+        .danger_accept_invalid_certs(!verify_cert)
+        .danger_accept_invalid_hostnames(ssl_mode != SslMode::VerifyFull);
+
+    Ok(MakeTlsConnector::new(builder.build()?))
+}
+
+fn read_pem(path: &Path) -> StdResult<Vec<u8>> {
+    fs::read(path).map_err(|err| format!("Cannot read {}: {err}", path.display()).into())
+}
+
+/// Replaces `sslmode` of the URL or key-value connection string with the value supported
+/// by `tokio-postgres`.
+fn replace_ssl_mode(connection_string: &str) -> MigraResult<(String, SslMode)> {
+    let is_url = connection_string.contains("://");
+    let mut offset = if is_url {
+        connection_string
+            .find('?')
+            .map_or(connection_string.len(), |pos| pos + 1)
+    } else {
+        0
+    };
+
+    let params = connection_string[offset..].split(|ch: char| {
+        if is_url {
+            ch == '&'
+        } else {
+            ch.is_ascii_whitespace()
+        }
+    });
+
+    for param in params {
+        if let Some(value) = param.strip_prefix(SSL_MODE_PARAM_PREFIX) {
+            let ssl_mode = SslMode::parse(value.trim_matches('\''))?;
+            let mut connection_string = connection_string.to_owned();
+            connection_string.replace_range(
+                offset + SSL_MODE_PARAM_PREFIX.len()..offset + param.len(),
+                ssl_mode.connection_value(),
+            );
+            return Ok((connection_string, ssl_mode));
+        }
+
+        // Separators are single-byte characters.
+        offset += param.len() + 1;
+    }
+
+    Ok((connection_string.to_owned(), SslMode::Prefer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replace_ssl_mode_in_url() -> MigraResult<()> {
+        assert_eq!(
+            replace_ssl_mode("postgres://postgres@localhost:6000/migra_tests")?,
+            (
+                String::from("postgres://postgres@localhost:6000/migra_tests"),
+                SslMode::Prefer
+            )
+        );
+        assert_eq!(
+            replace_ssl_mode("postgres://localhost/db?sslmode=verify-full&connect_timeout=10")?,
+            (
+                String::from("postgres://localhost/db?sslmode=require&connect_timeout=10"),
+                SslMode::VerifyFull
+            )
+        );
+        assert_eq!(
+            replace_ssl_mode("postgresql://localhost/db?sslmode=allow")?,
+            (
+                String::from("postgresql://localhost/db?sslmode=prefer"),
+                SslMode::Allow
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn replace_ssl_mode_in_key_value_string() -> MigraResult<()> {
+        assert_eq!(
+            replace_ssl_mode("host=localhost sslmode=verify-ca user=postgres")?,
+            (
+                String::from("host=localhost sslmode=require user=postgres"),
+                SslMode::VerifyCa
+            )
+        );
+        assert_eq!(
+            replace_ssl_mode("host=localhost sslmode='disable'")?,
+            (
+                String::from("host=localhost sslmode=disable"),
+                SslMode::Disable
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn cannot_use_unknown_ssl_mode() {
+        assert!(matches!(
+            replace_ssl_mode("postgres://localhost/db?sslmode=always"),
+            Err(Error::Db(_))
+        ));
+    }
+}
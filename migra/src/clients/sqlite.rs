@@ -1,16 +1,29 @@
+use super::table_name::{quote_identifier, TableName};
+use super::utils::quote_literal;
 use super::OpenDatabaseConnection;
 use crate::errors::{DbKind, Error, MigraResult, StdResult};
-use crate::managers::{BatchExecute, ManageMigrations, ManageTransaction};
+use crate::managers::{
+    BatchExecute, ManageLock, ManageMigrations, ManageTransaction, RenderMigrationsSql,
+};
 use crate::migration;
-use rusqlite::Connection;
+use rusqlite::{params, Connection};
+use std::path::PathBuf;
+use std::time::Duration;
 
 /// Predefined `Sqlite` client.
 ///
+/// Migrations lock is an exclusive transaction in the `<database file>.lock` database next to
+/// the database file. The lock is released when the client is dropped, and the operating
+/// system releases it if the process is killed, so there are no stale locks. The lock file
+/// itself is kept, because other processes may wait for the lock in it.
+///
 /// **Note:** Requires enabling `sqlite` feature.
 #[derive(Debug)]
 pub struct Client {
     conn: Connection,
-    migrations_table_name: String,
+    migrations_table: TableName,
+    lock_file_path: Option<PathBuf>,
+    lock_conn: Option<Connection>,
 }
 
 impl Client {
@@ -19,10 +32,23 @@ impl Client {
     pub fn conn(&self) -> &Connection {
         &self.conn
     }
+
+    fn migrations_version_table(&self) -> TableName {
+        self.migrations_table.with_suffix("_version")
+    }
+
+    fn table_exists(&self, table: &TableName) -> rusqlite::Result<bool> {
+        let stmt = format!(
+            "SELECT COUNT(*) > 0 FROM {}.sqlite_master WHERE type = 'table' AND name = $1",
+            quote_identifier(table.schema.as_deref().unwrap_or("main"), '"')
+        );
+        self.conn.query_row(&stmt, [&table.name], |row| row.get(0))
+    }
 }
 
 impl OpenDatabaseConnection for Client {
     fn manual(connection_string: &str, migrations_table_name: &str) -> MigraResult<Self> {
+        let migrations_table = TableName::parse(migrations_table_name)?;
         let conn = if connection_string == ":memory:" {
             Connection::open_in_memory()
         } else {
@@ -30,14 +56,40 @@ impl OpenDatabaseConnection for Client {
         }
         .map_err(|err| Error::db(err.into(), DbKind::DatabaseConnection))?;
 
+        let lock_file_path = database_file_path(&conn)
+            .map_err(|err| Error::db(err.into(), DbKind::DatabaseConnection))?
+            .map(|db_path| {
+                let mut lock_file_name = db_path.into_os_string();
+                lock_file_name.push(".lock");
+                PathBuf::from(lock_file_name)
+            });
+
+        tracing::debug!(
+            client = "Sqlite",
+            migrations_table = migrations_table_name,
+            "Connected to database"
+        );
         Ok(Client {
             conn,
-            migrations_table_name: migrations_table_name.to_owned(),
+            migrations_table,
+            lock_file_path,
+            lock_conn: None,
         })
     }
 }
 
+/// Returns path to the main database file, that is resolved by `SQLite` from the file name or
+/// `file:` URI. In-memory and temporary databases have no file.
+fn database_file_path(conn: &Connection) -> rusqlite::Result<Option<PathBuf>> {
+    let file: String = conn.query_row("PRAGMA database_list", [], |row| row.get(2))?;
+    Ok(Some(file)
+        .filter(|file| !file.is_empty())
+        .map(PathBuf::from))
+}
+
 impl BatchExecute for Client {
+    // SQLite doesn't report the position of the error, so the failed statement is unknown and
+    // only the error message is reported.
     fn batch_execute(&mut self, sql: &str) -> StdResult<()> {
         self.conn.execute_batch(sql).map_err(From::from)
     }
@@ -45,28 +97,113 @@ impl BatchExecute for Client {
 
 impl ManageTransaction for Client {}
 
+impl ManageLock for Client {
+    fn acquire_lock(&mut self, timeout: Duration) -> MigraResult<()> {
+        // In-memory database cannot be shared between processes.
+        let lock_file_path = match self.lock_file_path {
+            Some(ref lock_file_path) if self.lock_conn.is_none() => lock_file_path,
+            _ => return Ok(()),
+        };
+
+        let lock_conn = Connection::open(lock_file_path)
+            .and_then(|lock_conn| {
+                // Busy lock is retried by `wait_for_lock` to report the timeout.
+                lock_conn.busy_timeout(Duration::ZERO)?;
+                Ok(lock_conn)
+            })
+            .map_err(|err| Error::db(err.into(), DbKind::AcquireLock))?;
+        super::utils::wait_for_lock(timeout, || {
+            match lock_conn.execute_batch("BEGIN EXCLUSIVE") {
+                Ok(()) => Ok(true),
+                Err(rusqlite::Error::SqliteFailure(err, _))
+                    if err.code == rusqlite::ErrorCode::DatabaseBusy =>
+                {
+                    Ok(false)
+                }
+                Err(err) => Err(err.into()),
+            }
+        })?;
+
+        self.lock_conn = Some(lock_conn);
+        Ok(())
+    }
+
+    fn release_lock(&mut self) -> MigraResult<()> {
+        match self.lock_conn.take() {
+            Some(lock_conn) => lock_conn
+                .execute_batch("ROLLBACK")
+                .map_err(|err| Error::db(err.into(), DbKind::ReleaseLock)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        self.release_lock().ok();
+    }
+}
+
 impl ManageMigrations for Client {
-    fn create_migrations_table(&mut self) -> MigraResult<()> {
-        let stmt = format!(
-            r#"CREATE TABLE IF NOT EXISTS {} (
-                id      int             AUTO_INCREMENT PRIMARY KEY,
-                name    varchar(256)    NOT NULL UNIQUE
-            )"#,
-            &self.migrations_table_name
-        );
+    fn get_migrations_table_version(&mut self) -> MigraResult<u32> {
+        let version_table = self.migrations_version_table();
 
-        self.batch_execute(&stmt)
-            .map_err(|err| Error::db(err, DbKind::CreateMigrationsTable))
+        self.table_exists(&version_table)
+            .and_then(|version_table_exists| {
+                if version_table_exists {
+                    let stmt = format!("SELECT max(version) FROM {}", version_table.quoted('"'));
+                    self.conn
+                        .query_row(&stmt, [], |row| row.get::<_, Option<u32>>(0))
+                        .map(Option::unwrap_or_default)
+                } else {
+                    self.table_exists(&self.migrations_table).map(u32::from)
+                }
+            })
+            .map_err(|err| Error::db(err.into(), DbKind::GetMigrationsTableVersion))
+    }
+
+    fn upgrade_migrations_table(&mut self, version: u32) -> MigraResult<()> {
+        let Some(stmt) = self.upgrade_migrations_table_sql(version) else {
+            return Ok(());
+        };
+
+        self.conn
+            .savepoint()
+            .and_then(|savepoint| {
+                savepoint.execute_batch(&stmt)?;
+                savepoint.commit()
+            })
+            .map_err(|err| {
+                Error::db(
+                    err.into(),
+                    super::utils::upgrade_migrations_table_error_kind(version),
+                )
+            })
     }
 
-    fn insert_migration(&mut self, name: &str) -> MigraResult<u64> {
+    fn insert_migration(
+        &mut self,
+        name: &str,
+        checksum: &str,
+        duration: Duration,
+        applied_by: &str,
+    ) -> MigraResult<u64> {
         let stmt = format!(
-            "INSERT INTO {} (name) VALUES ($1)",
-            &self.migrations_table_name
+            r"INSERT INTO {} (name, checksum, applied_at, duration_ms, applied_by)
+            VALUES ($1, $2, CURRENT_TIMESTAMP, $3, $4)",
+            self.migrations_table.quoted('"')
         );
 
         self.conn
-            .execute(&stmt, [name])
+            .execute(
+                &stmt,
+                params![
+                    name,
+                    checksum,
+                    super::utils::duration_to_millis(duration),
+                    applied_by
+                ],
+            )
             .map(|res| res as u64)
             .map_err(|err| Error::db(err.into(), DbKind::InsertMigration))
     }
@@ -74,7 +211,7 @@ impl ManageMigrations for Client {
     fn delete_migration(&mut self, name: &str) -> MigraResult<u64> {
         let stmt = format!(
             "DELETE FROM {} WHERE name = $1",
-            &self.migrations_table_name
+            self.migrations_table.quoted('"')
         );
 
         self.conn
@@ -83,21 +220,406 @@ impl ManageMigrations for Client {
             .map_err(|err| Error::db(err.into(), DbKind::DeleteMigration))
     }
 
-    fn get_applied_migrations(&mut self) -> MigraResult<migration::List> {
+    fn get_migrations_history(&mut self) -> MigraResult<Vec<migration::AppliedMigration>> {
+        let version = self.get_migrations_table_version()?;
+        if version == 0 {
+            return Ok(Vec::new());
+        }
+
+        // Legacy tables don't have columns of the later versions and their `id` is `NULL`.
+        let checksum = if version >= 2 { "checksum" } else { "NULL" };
+        let metadata = if version >= 3 {
+            "CAST(strftime('%s', applied_at) AS integer), duration_ms, applied_by"
+        } else {
+            "NULL, NULL, NULL"
+        };
+        let order = if version >= 4 { "id" } else { "rowid" };
         let stmt = format!(
-            "SELECT name FROM {} ORDER BY id DESC",
-            &self.migrations_table_name
+            "SELECT name, {checksum}, {metadata} FROM {} ORDER BY {order} DESC",
+            self.migrations_table.quoted('"')
         );
 
         self.conn
             .prepare(&stmt)
             .and_then(|mut stmt| {
-                stmt.query_map([], |row| row.get(0))?
-                    .collect::<Result<Vec<String>, _>>()
+                stmt.query_map([], |row| {
+                    let name: String = row.get(0)?;
+                    let checksum: Option<String> = row.get(1)?;
+                    Ok(super::utils::applied_migration_from_row(
+                        &name,
+                        checksum.as_deref(),
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                    ))
+                })?
+                .collect()
             })
-            .map(From::from)
             .map_err(|err| Error::db(err.into(), DbKind::GetAppliedMigrations))
     }
 }
 
+impl RenderMigrationsSql for Client {
+    fn upgrade_migrations_table_sql(&self, version: u32) -> Option<String> {
+        let table_name = self.migrations_table.quoted('"');
+        let stmt = match version {
+            1 => format!(
+                r"CREATE TABLE IF NOT EXISTS {table_name} (
+                    id      int             AUTO_INCREMENT PRIMARY KEY,
+                    name    varchar(256)    NOT NULL UNIQUE
+                );"
+            ),
+            2 => format!("ALTER TABLE {table_name} ADD COLUMN checksum varchar(64);"),
+            3 => format!(
+                r"ALTER TABLE {table_name} ADD COLUMN applied_at datetime;
+                ALTER TABLE {table_name} ADD COLUMN duration_ms bigint;
+                ALTER TABLE {table_name} ADD COLUMN applied_by varchar(256);"
+            ),
+            // `int AUTO_INCREMENT PRIMARY KEY` is not an alias for the `rowid`, so `id`
+            // always was `NULL`. The `rowid` keeps the insertion order of the old rows.
+            4 => format!(
+                r"CREATE TABLE {tmp_table_name} (
+                    id          integer         PRIMARY KEY AUTOINCREMENT,
+                    name        varchar(256)    NOT NULL UNIQUE,
+                    checksum    varchar(64),
+                    applied_at  datetime,
+                    duration_ms bigint,
+                    applied_by  varchar(256)
+                );
+                INSERT INTO {tmp_table_name} (name, checksum, applied_at, duration_ms, applied_by)
+                    SELECT name, checksum, applied_at, duration_ms, applied_by
+                    FROM {table_name} ORDER BY rowid;
+                DROP TABLE {table_name};
+                ALTER TABLE {tmp_table_name} RENAME TO {unqualified_table_name};",
+                tmp_table_name = self.migrations_table.with_suffix("_tmp").quoted('"'),
+                unqualified_table_name = quote_identifier(&self.migrations_table.name, '"'),
+            ),
+            _ => return None,
+        };
+
+        let version_table_name = self.migrations_version_table().quoted('"');
+        Some(format!(
+            r"{stmt}
+            CREATE TABLE IF NOT EXISTS {version_table_name} (version integer NOT NULL);
+            DELETE FROM {version_table_name};
+            INSERT INTO {version_table_name} (version) VALUES ({version});"
+        ))
+    }
+
+    fn insert_migration_sql(
+        &self,
+        name: &str,
+        checksum: &str,
+        duration: Duration,
+        applied_by: &str,
+    ) -> String {
+        format!(
+            r"INSERT INTO {} (name, checksum, applied_at, duration_ms, applied_by)
+            VALUES ({}, {}, CURRENT_TIMESTAMP, {}, {})",
+            self.migrations_table.quoted('"'),
+            quote_literal(name),
+            quote_literal(checksum),
+            super::utils::duration_to_millis(duration),
+            quote_literal(applied_by)
+        )
+    }
+
+    fn delete_migration_sql(&self, name: &str) -> String {
+        format!(
+            "DELETE FROM {} WHERE name = {}",
+            self.migrations_table.quoted('"'),
+            quote_literal(name)
+        )
+    }
+}
+
 impl super::Client for Client {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clients::OpenDatabaseConnection;
+    use crate::managers::MIGRATIONS_TABLE_VERSION;
+    use std::fs;
+
+    const FIRST_MIGRATION: &str = "210218232851_create_articles";
+    const SECOND_MIGRATION: &str = "210218233414_create_persons";
+
+    fn applied_migration_names(client: &mut Client) -> MigraResult<Vec<String>> {
+        client.get_applied_migrations().map(|migrations| {
+            migrations
+                .iter()
+                .map(|migration| migration.name().clone())
+                .collect()
+        })
+    }
+
+    #[test]
+    fn create_latest_migrations_table() -> MigraResult<()> {
+        let mut client = Client::new(":memory:")?;
+
+        assert_eq!(client.get_migrations_table_version()?, 0);
+
+        client.create_migrations_table()?;
+        assert_eq!(
+            client.get_migrations_table_version()?,
+            MIGRATIONS_TABLE_VERSION
+        );
+
+        client.create_migrations_table()?;
+        assert_eq!(
+            client.get_migrations_table_version()?,
+            MIGRATIONS_TABLE_VERSION
+        );
+
+        client.run_upgrade_migration(FIRST_MIGRATION, "SELECT 1;")?;
+
+        let history = client.get_migrations_history()?;
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].migration().name(), FIRST_MIGRATION);
+        assert_eq!(
+            history[0].migration().checksum(),
+            Some(migration::calculate_checksum("SELECT 1;").as_str())
+        );
+        assert!(history[0].applied_at().is_some());
+        assert!(history[0].duration().is_some());
+        assert!(history[0].applied_by().is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_history_of_legacy_migrations_table() -> MigraResult<()> {
+        let mut client = Client::new(":memory:")?;
+        assert!(client.get_migrations_history()?.is_empty());
+
+        client
+            .batch_execute(&format!(
+                r"CREATE TABLE migrations (
+                    id      int             AUTO_INCREMENT PRIMARY KEY,
+                    name    varchar(256)    NOT NULL UNIQUE
+                );
+                INSERT INTO migrations (name) VALUES ('{FIRST_MIGRATION}');
+                INSERT INTO migrations (name) VALUES ('{SECOND_MIGRATION}');"
+            ))
+            .map_err(|err| Error::db(err, DbKind::ApplySql))?;
+
+        let history = client.get_migrations_history()?;
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].migration().name(), SECOND_MIGRATION);
+        assert_eq!(history[0].migration().checksum(), None);
+        assert_eq!(history[0].applied_at(), None);
+        assert_eq!(history[1].migration().name(), FIRST_MIGRATION);
+
+        Ok(())
+    }
+
+    #[test]
+    fn upgrade_legacy_migrations_table() -> MigraResult<()> {
+        let mut client = Client::new(":memory:")?;
+
+        client
+            .batch_execute(&format!(
+                r"CREATE TABLE migrations (
+                    id      int             AUTO_INCREMENT PRIMARY KEY,
+                    name    varchar(256)    NOT NULL UNIQUE
+                );
+                INSERT INTO migrations (name) VALUES ('{FIRST_MIGRATION}');"
+            ))
+            .map_err(|err| Error::db(err, DbKind::ApplySql))?;
+
+        assert_eq!(client.get_migrations_table_version()?, 1);
+
+        client.create_migrations_table()?;
+        assert_eq!(
+            client.get_migrations_table_version()?,
+            MIGRATIONS_TABLE_VERSION
+        );
+
+        client.run_upgrade_migration(SECOND_MIGRATION, "SELECT 1;")?;
+
+        let history = client.get_migrations_history()?;
+        assert_eq!(history.len(), 2);
+
+        let legacy_migration = history
+            .iter()
+            .find(|applied| applied.migration().name() == FIRST_MIGRATION)
+            .expect("Cannot find legacy migration");
+        assert_eq!(legacy_migration.migration().checksum(), None);
+        assert_eq!(legacy_migration.applied_at(), None);
+        assert_eq!(legacy_migration.duration(), None);
+        assert_eq!(legacy_migration.applied_by(), None);
+
+        assert_eq!(
+            applied_migration_names(&mut client)?,
+            vec![SECOND_MIGRATION, FIRST_MIGRATION]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn keep_order_of_many_applied_migrations() -> MigraResult<()> {
+        let mut client = Client::new(":memory:")?;
+        client.create_migrations_table()?;
+
+        let names = (1..=50)
+            .map(|index| format!("{index:03}_migration"))
+            .collect::<Vec<_>>();
+        for name in &names {
+            client.run_upgrade_migration(name, "SELECT 1;")?;
+        }
+
+        let mut expected_names = names.clone();
+        expected_names.reverse();
+        assert_eq!(applied_migration_names(&mut client)?, expected_names);
+
+        let mut applied_migrations = client.get_applied_migrations()?;
+        for name in names.iter().rev() {
+            let latest_name = applied_migrations[0].name().clone();
+            assert_eq!(&latest_name, name);
+
+            client.run_downgrade_migration(&latest_name, "SELECT 1;")?;
+            applied_migrations = client.get_applied_migrations()?;
+        }
+        assert!(applied_migrations.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn convert_migrations_table_without_autoincrement() -> MigraResult<()> {
+        let mut client = Client::new(":memory:")?;
+
+        for version in 1..MIGRATIONS_TABLE_VERSION {
+            client.upgrade_migrations_table(version)?;
+        }
+
+        let names = (1..=50)
+            .map(|index| format!("{:03}_migration", 51 - index))
+            .collect::<Vec<_>>();
+        for name in &names {
+            client.run_upgrade_migration(name, "SELECT 1;")?;
+        }
+
+        client.create_migrations_table()?;
+        assert_eq!(
+            client.get_migrations_table_version()?,
+            MIGRATIONS_TABLE_VERSION
+        );
+
+        let mut expected_names = names.clone();
+        expected_names.reverse();
+        assert_eq!(applied_migration_names(&mut client)?, expected_names);
+
+        client.run_upgrade_migration("051_migration", "SELECT 1;")?;
+        assert_eq!(
+            applied_migration_names(&mut client)?.first(),
+            Some(&String::from("051_migration"))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn use_schema_qualified_migrations_table() -> MigraResult<()> {
+        let mut client = Client::manual(":memory:", "main.schema_migrations")?;
+        client.create_migrations_table()?;
+        client.run_upgrade_migration(FIRST_MIGRATION, "SELECT 1;")?;
+
+        assert_eq!(applied_migration_names(&mut client)?, vec![FIRST_MIGRATION]);
+        let table_exists = |table_name| {
+            client
+                .table_exists(&TableName::parse(table_name)?)
+                .map_err(|err| Error::db(err.into(), DbKind::GetMigrationsTableVersion))
+        };
+        assert!(table_exists("schema_migrations")?);
+        assert!(!table_exists("migrations")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn cannot_use_invalid_migrations_table_name() {
+        assert!(matches!(
+            Client::manual(":memory:", "migrations; DROP TABLE users"),
+            Err(Error::InvalidTableName(_))
+        ));
+    }
+
+    #[test]
+    fn report_sql_error_without_failed_statement() -> MigraResult<()> {
+        let mut client = Client::new(":memory:")?;
+
+        assert!(matches!(
+            client.apply_sql("SELECT 1;\nSELECT * FROM unknown;"),
+            Err(Error::Db(ref error)) if error.statement().is_none()
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn wait_for_released_lock() -> MigraResult<()> {
+        let db_path = std::env::temp_dir().join(format!("migra_lock_{}.db", std::process::id()));
+        let db_path = db_path.to_str().unwrap();
+
+        let mut first_client = Client::new(db_path)?;
+        let mut second_client = Client::new(db_path)?;
+
+        first_client.acquire_lock(Duration::from_secs(1))?;
+        assert!(second_client
+            .acquire_lock(Duration::from_millis(200))
+            .is_err());
+
+        first_client.release_lock()?;
+        second_client.acquire_lock(Duration::from_secs(1))?;
+        second_client.release_lock()?;
+
+        drop(first_client);
+        drop(second_client);
+        fs::remove_file(db_path).ok();
+        fs::remove_file(format!("{db_path}.lock")).ok();
+
+        Ok(())
+    }
+
+    #[test]
+    fn release_lock_of_dropped_client() -> MigraResult<()> {
+        let db_path =
+            std::env::temp_dir().join(format!("migra_lock_drop_{}.db", std::process::id()));
+        let db_path = db_path.to_str().unwrap();
+
+        let mut first_client = Client::new(db_path)?;
+        first_client.acquire_lock(Duration::from_secs(1))?;
+        drop(first_client);
+
+        let mut second_client = Client::new(db_path)?;
+        second_client.acquire_lock(Duration::from_millis(200))?;
+        drop(second_client);
+
+        fs::remove_file(db_path).ok();
+        fs::remove_file(format!("{db_path}.lock")).ok();
+
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_lock_file_path_of_file_uri() -> MigraResult<()> {
+        let db_path = std::env::temp_dir().join(format!("migra_uri_{}.db", std::process::id()));
+
+        let client = Client::new(&format!("file:{}?mode=rwc", db_path.display()))?;
+        assert_eq!(
+            client.lock_file_path,
+            Some(PathBuf::from(format!("{}.lock", db_path.display())))
+        );
+        drop(client);
+
+        let client = Client::new("file::memory:")?;
+        assert_eq!(client.lock_file_path, None);
+
+        fs::remove_file(db_path).ok();
+
+        Ok(())
+    }
+}
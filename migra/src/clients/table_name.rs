@@ -0,0 +1,134 @@
+use crate::errors::{Error, MigraResult};
+use std::fmt;
+
+/// Maximum length of table and schema names. `Postgres` limits identifiers to 63 bytes,
+/// so the name of the migrations version table with `_version` suffix should fit too.
+const MAX_IDENTIFIER_LEN: usize = 55;
+
+/// Validated name of the migrations table with an optional schema, e.g. `migrations` or
+/// `ops.schema_migrations`.
+///
+/// Names can contain only ASCII letters, digits, `_` and `$` and cannot start with
+/// a digit, so they are safe to be quoted and inserted into SQL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct TableName {
+    pub(super) schema: Option<String>,
+    pub(super) name: String,
+}
+
+impl TableName {
+    /// Parses and validates the table name.
+    pub(super) fn parse(table_name: &str) -> MigraResult<Self> {
+        let (schema, name) = match table_name.split_once('.') {
+            Some((schema, name)) => (Some(schema), name),
+            None => (None, table_name),
+        };
+
+        if !schema.map_or(true, is_valid_identifier) || !is_valid_identifier(name) {
+            return Err(Error::InvalidTableName(table_name.to_owned()));
+        }
+
+        Ok(TableName {
+            schema: schema.map(String::from),
+            name: name.to_owned(),
+        })
+    }
+
+    /// Returns a table name in the same schema with the name suffix, e.g. the migrations
+    /// version table.
+    pub(super) fn with_suffix(&self, suffix: &str) -> Self {
+        TableName {
+            schema: self.schema.clone(),
+            name: format!("{}{suffix}", &self.name),
+        }
+    }
+
+    /// Returns a schema-qualified table name with quoted identifiers, e.g. `"ops"."migrations"`
+    /// for `Postgres` and `Sqlite` or `` `ops`.`migrations` `` for `MySQL`.
+    pub(super) fn quoted(&self, quote: char) -> String {
+        match self.schema {
+            Some(ref schema) => format!(
+                "{}.{}",
+                quote_identifier(schema, quote),
+                quote_identifier(&self.name, quote)
+            ),
+            None => quote_identifier(&self.name, quote),
+        }
+    }
+}
+
+impl fmt::Display for TableName {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.schema {
+            Some(ref schema) => write!(fmt, "{schema}.{}", &self.name),
+            None => fmt.write_str(&self.name),
+        }
+    }
+}
+
+/// Quotes the validated identifier.
+pub(super) fn quote_identifier(identifier: &str, quote: char) -> String {
+    format!("{quote}{identifier}{quote}")
+}
+
+fn is_valid_identifier(identifier: &str) -> bool {
+    identifier.len() <= MAX_IDENTIFIER_LEN
+        && identifier
+            .chars()
+            .next()
+            .is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_')
+        && identifier
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '$')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_table_name() -> MigraResult<()> {
+        let table_name = TableName::parse("migrations")?;
+        assert_eq!(table_name.schema, None);
+        assert_eq!(table_name.name, "migrations");
+        assert_eq!(table_name.to_string(), "migrations");
+        assert_eq!(table_name.quoted('"'), r#""migrations""#);
+
+        let table_name = TableName::parse("ops.schema_migrations")?;
+        assert_eq!(table_name.schema.as_deref(), Some("ops"));
+        assert_eq!(table_name.name, "schema_migrations");
+        assert_eq!(table_name.to_string(), "ops.schema_migrations");
+        assert_eq!(table_name.quoted('`'), "`ops`.`schema_migrations`");
+        assert_eq!(
+            table_name.with_suffix("_version").quoted('"'),
+            r#""ops"."schema_migrations_version""#
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn cannot_parse_invalid_table_name() {
+        for table_name in [
+            "",
+            "ops.",
+            ".migrations",
+            "a.b.c",
+            "1migrations",
+            "migrations; DROP TABLE users",
+            r#"migrations""#,
+            "migrations`",
+            "schema migrations",
+            &"m".repeat(MAX_IDENTIFIER_LEN + 1),
+        ] {
+            assert!(
+                matches!(
+                    TableName::parse(table_name),
+                    Err(Error::InvalidTableName(_))
+                ),
+                "{:?} must be invalid",
+                table_name
+            );
+        }
+    }
+}
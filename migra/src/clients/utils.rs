@@ -0,0 +1,89 @@
+use crate::errors::{DbKind, Error, MigraResult, StdResult};
+use crate::migration;
+use std::convert::TryFrom;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the lock is retried while waiting for it.
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Returns an error kind for the failed upgrade of the migrations table to the specific version.
+pub(super) fn upgrade_migrations_table_error_kind(version: u32) -> DbKind {
+    if version == 1 {
+        DbKind::CreateMigrationsTable
+    } else {
+        DbKind::UpgradeMigrationsTable
+    }
+}
+
+/// Converts the migration duration to milliseconds that are stored in the migrations table.
+pub(super) fn duration_to_millis(duration: Duration) -> i64 {
+    i64::try_from(duration.as_millis()).unwrap_or(i64::MAX)
+}
+
+/// Quotes the string literal to inline it into SQL that is rendered instead of executed.
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+pub(super) fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Builds an applied migration from the raw values of the migrations table row.
+///
+/// The applied time is presented as seconds since the unix epoch and the duration
+/// is presented in milliseconds.
+pub(super) fn applied_migration_from_row(
+    name: &str,
+    checksum: Option<&str>,
+    applied_at: Option<i64>,
+    duration: Option<i64>,
+    applied_by: Option<String>,
+) -> migration::AppliedMigration {
+    let migration = checksum.map_or_else(
+        || migration::Migration::new(name),
+        |checksum| migration::Migration::with_checksum(name, checksum),
+    );
+    let applied_at = applied_at
+        .and_then(|secs| u64::try_from(secs).ok())
+        .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs));
+    let duration = duration
+        .and_then(|millis| u64::try_from(millis).ok())
+        .map(Duration::from_millis);
+
+    migration::AppliedMigration::new(migration, applied_at, duration, applied_by)
+}
+
+/// Tries to acquire a lock until it succeeds or the timeout expires.
+///
+/// The `try_lock` function returns `true` if the lock was acquired and `false`
+/// if the lock is held by someone else.
+pub(super) fn wait_for_lock<TryLockFn>(
+    timeout: Duration,
+    mut try_lock: TryLockFn,
+) -> MigraResult<()>
+where
+    TryLockFn: FnMut() -> StdResult<bool>,
+{
+    let started_at = Instant::now();
+
+    loop {
+        if try_lock().map_err(|err| Error::db(err, DbKind::AcquireLock))? {
+            return Ok(());
+        }
+
+        match timeout.checked_sub(started_at.elapsed()) {
+            Some(remaining) if !remaining.is_zero() => {
+                thread::sleep(LOCK_RETRY_INTERVAL.min(remaining));
+            }
+            _ => {
+                return Err(Error::db(
+                    format!(
+                        "Timed out after {}s waiting for another migration process",
+                        timeout.as_secs()
+                    )
+                    .into(),
+                    DbKind::AcquireLock,
+                ))
+            }
+        }
+    }
+}
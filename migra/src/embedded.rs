@@ -0,0 +1,197 @@
+use crate::clients::Client;
+use crate::errors::MigraResult;
+use crate::migration;
+use crate::migrator::{Direction, MigrationSource, Migrator, Target};
+use std::io;
+
+/// Migration with SQL content that was embedded into the binary at compile time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmbeddedMigration {
+    name: &'static str,
+    upgrade_sql: &'static str,
+    downgrade_sql: &'static str,
+    line_offsets: (usize, usize),
+}
+
+impl EmbeddedMigration {
+    /// Creates new embedded migration with SQL content of `up.sql` and `down.sql` files.
+    #[must_use]
+    pub const fn new(
+        name: &'static str,
+        upgrade_sql: &'static str,
+        downgrade_sql: &'static str,
+    ) -> Self {
+        EmbeddedMigration {
+            name,
+            upgrade_sql,
+            downgrade_sql,
+            line_offsets: (0, 0),
+        }
+    }
+
+    /// Sets numbers of lines before the upgrade and downgrade SQL content in the single
+    /// migration file.
+    ///
+    /// See [`Migration::with_line_offsets`] for more information.
+    ///
+    /// [`Migration::with_line_offsets`]: migration::Migration::with_line_offsets
+    #[must_use]
+    pub const fn with_line_offsets(mut self, upgrade: usize, downgrade: usize) -> Self {
+        self.line_offsets = (upgrade, downgrade);
+        self
+    }
+
+    /// Returns name of migration.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns SQL content to upgrade database schema.
+    #[must_use]
+    pub fn upgrade_sql(&self) -> &'static str {
+        self.upgrade_sql
+    }
+
+    /// Returns SQL content to downgrade database schema.
+    #[must_use]
+    pub fn downgrade_sql(&self) -> &'static str {
+        self.downgrade_sql
+    }
+
+    /// Returns migration with SQL content and checksum of the upgrade SQL content.
+    #[must_use]
+    pub fn migration(&self) -> migration::Migration {
+        migration::Migration::with_sql(self.name, self.upgrade_sql, self.downgrade_sql)
+            .with_line_offsets(self.line_offsets.0, self.line_offsets.1)
+    }
+}
+
+/// A static set of embedded migrations sorted by version.
+///
+/// Usually is created by [`embed_migrations`] macro.
+///
+/// [embed_migrations]: crate::embed_migrations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmbeddedMigrations {
+    inner: &'static [EmbeddedMigration],
+}
+
+impl EmbeddedMigrations {
+    /// Creates a set of embedded migrations. Migrations must be sorted by version.
+    #[must_use]
+    pub const fn new(migrations: &'static [EmbeddedMigration]) -> Self {
+        EmbeddedMigrations { inner: migrations }
+    }
+
+    /// Returns all embedded migrations.
+    #[must_use]
+    pub fn migrations(&self) -> &'static [EmbeddedMigration] {
+        self.inner
+    }
+
+    /// Returns embedded migration with specific name.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&'static EmbeddedMigration> {
+        self.inner.iter().find(|migration| migration.name == name)
+    }
+
+    /// Returns all embedded migrations as [List] with checksums.
+    ///
+    /// [List]: migration::List
+    #[must_use]
+    pub fn list(&self) -> migration::List {
+        self.inner
+            .iter()
+            .map(EmbeddedMigration::migration)
+            .collect()
+    }
+
+    /// Brings the database up to date. Creates the migrations table if it doesn't exist and
+    /// applies all pending migrations.
+    ///
+    /// See [`Migrator`] for more information.
+    ///
+    /// Returns the list of applied migrations.
+    pub fn run_pending_migrations(&self, client: &mut dyn Client) -> MigraResult<migration::List> {
+        Migrator::new(client, self).upgrade(&Target::All)
+    }
+
+    fn get_or_not_found(
+        &self,
+        migration: &migration::Migration,
+    ) -> MigraResult<&'static EmbeddedMigration> {
+        self.get(migration.name()).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Migration {} is not embedded", migration.name()),
+            )
+            .into()
+        })
+    }
+}
+
+impl MigrationSource for EmbeddedMigrations {
+    fn all_migrations(&self) -> MigraResult<migration::List> {
+        Ok(self.list())
+    }
+
+    fn upgrade_sql(&self, migration: &migration::Migration) -> MigraResult<String> {
+        self.get_or_not_found(migration)
+            .map(|embedded_migration| embedded_migration.upgrade_sql.to_owned())
+    }
+
+    fn downgrade_sql(&self, migration: &migration::Migration) -> MigraResult<String> {
+        self.get_or_not_found(migration)
+            .map(|embedded_migration| embedded_migration.downgrade_sql.to_owned())
+    }
+
+    fn line_offset(
+        &self,
+        migration: &migration::Migration,
+        direction: Direction,
+    ) -> MigraResult<usize> {
+        self.get_or_not_found(migration)
+            .map(|embedded_migration| match direction {
+                Direction::Upgrade => embedded_migration.line_offsets.0,
+                Direction::Downgrade => embedded_migration.line_offsets.1,
+            })
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+    use crate::clients::{OpenDatabaseConnection, SqliteClient};
+    use crate::managers::ManageMigrations;
+
+    const MIGRATIONS: EmbeddedMigrations = EmbeddedMigrations::new(&[
+        EmbeddedMigration::new(
+            "210218232851_create_articles",
+            "CREATE TABLE articles (title text NOT NULL);",
+            "DROP TABLE articles;",
+        ),
+        EmbeddedMigration::new(
+            "210218233414_create_persons",
+            "CREATE TABLE persons (name text NOT NULL);",
+            "DROP TABLE persons;",
+        ),
+    ]);
+
+    #[test]
+    fn run_pending_embedded_migrations() -> MigraResult<()> {
+        let mut client = SqliteClient::new(":memory:")?;
+
+        let applied_migrations = MIGRATIONS.run_pending_migrations(&mut client)?;
+        assert_eq!(applied_migrations, MIGRATIONS.list());
+        assert_eq!(
+            client.get_applied_migrations()?,
+            MIGRATIONS.list().iter().rev().collect()
+        );
+
+        let applied_migrations = MIGRATIONS.run_pending_migrations(&mut client)?;
+        assert_eq!(applied_migrations, migration::List::new());
+
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 use std::fmt;
 use std::io;
+use std::ops::Range;
 
 /// A helper type for any standard error.
 pub type StdError = Box<dyn std::error::Error + 'static + Sync + Send>;
@@ -17,6 +18,29 @@ pub enum Error {
     /// Represents database errors.
     Db(DbError),
 
+    /// Represents errors of migration versions.
+    Version(VersionError),
+
+    /// Applied migration is irreversible and cannot be rolled back.
+    Irreversible(String),
+
+    /// Applied migrations were changed in the migration source after they were applied.
+    Drifted(Vec<String>),
+
+    /// Migrations table name is not a valid identifier.
+    InvalidTableName(String),
+
+    /// Placeholder in migration SQL has no value.
+    UnresolvedPlaceholder {
+        /// Name of the placeholder.
+        name: String,
+        /// Name of the migration that contains the placeholder.
+        migration: Option<String>,
+    },
+
+    /// Represents errors of migration runs that were applied partially.
+    PartialRun(PartialRunError),
+
     /// Represents standard input output errors.
     Io(io::Error),
 }
@@ -24,8 +48,31 @@ pub enum Error {
 impl fmt::Display for Error {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Error::Db(ref error) => write!(fmt, "{}", error),
-            Error::Io(ref error) => write!(fmt, "{}", error),
+            Error::Db(ref error) => write!(fmt, "{error}"),
+            Error::Version(ref error) => write!(fmt, "{error}"),
+            Error::Irreversible(ref name) => write!(
+                fmt,
+                r#"Migration "{name}" is irreversible and cannot be rolled back"#
+            ),
+            Error::Drifted(ref names) => {
+                write!(fmt, "Applied migrations were changed: {}", names.join(", "))
+            }
+            Error::InvalidTableName(ref name) => write!(
+                fmt,
+                r#"Invalid migrations table name "{name}". Table and optional schema names separated by "." must start with a letter or "_" and contain only letters, digits, "_" and "$" up to 55 characters"#
+            ),
+            Error::UnresolvedPlaceholder {
+                ref name,
+                ref migration,
+            } => {
+                write!(fmt, r#"Placeholder "${{{name}}}""#)?;
+                if let Some(ref migration) = migration {
+                    write!(fmt, r#" in "{migration}" migration"#)?;
+                }
+                fmt.write_str(" has no value")
+            }
+            Error::PartialRun(ref error) => write!(fmt, "{error}"),
+            Error::Io(ref error) => write!(fmt, "{error}"),
         }
     }
 }
@@ -46,10 +93,74 @@ impl From<io::Error> for Error {
 }
 
 impl Error {
-    /// Creates a database error.
+    /// Creates a database error. Details of [`SqlError`] are moved to the database error.
     #[must_use]
     pub fn db(origin: StdError, kind: DbKind) -> Self {
-        Error::Db(DbError { kind, origin })
+        let (origin, details) = match origin.downcast::<SqlError>() {
+            Ok(sql_error) => {
+                let SqlError { origin, details } = *sql_error;
+                (origin, details)
+            }
+            Err(origin) => (origin, SqlErrorDetails::default()),
+        };
+
+        Error::Db(DbError {
+            kind,
+            origin,
+            migration: None,
+            details: Box::new(details),
+        })
+    }
+
+    /// Creates an error of the placeholder without value.
+    #[must_use]
+    pub(crate) fn unresolved_placeholder(name: &str) -> Self {
+        Error::UnresolvedPlaceholder {
+            name: name.to_owned(),
+            migration: None,
+        }
+    }
+
+    /// Sets the name of the migration, in which the database error or the placeholder
+    /// without value occurred.
+    #[must_use]
+    pub(crate) fn with_migration(mut self, name: &str) -> Self {
+        match self {
+            Error::Db(ref mut error) => error.migration = Some(name.to_owned()),
+            Error::UnresolvedPlaceholder {
+                ref mut migration, ..
+            } => *migration = Some(name.to_owned()),
+            _ => {}
+        }
+        self
+    }
+
+    /// Moves the line of the failed statement by the number of lines before the applied SQL
+    /// in the migration source.
+    #[must_use]
+    pub(crate) fn with_line_offset(mut self, line_offset: usize) -> Self {
+        if let Error::Db(ref mut error) = self {
+            if let Some(ref mut statement) = error.details.statement {
+                statement.line += line_offset;
+            }
+        }
+        self
+    }
+
+    /// Creates an error of the migration run that was applied partially.
+    #[must_use]
+    pub fn partial_run(
+        failed: &str,
+        committed: Vec<String>,
+        in_transaction: bool,
+        origin: Error,
+    ) -> Self {
+        Error::PartialRun(PartialRunError {
+            failed: failed.to_owned(),
+            committed,
+            in_transaction,
+            origin: Box::new(origin),
+        })
     }
 }
 
@@ -69,9 +180,21 @@ pub enum DbKind {
     /// Failed to rollback transaction.
     RollbackTransaction,
 
+    /// Failed to acquire a migrations lock.
+    AcquireLock,
+
+    /// Failed to release a migrations lock.
+    ReleaseLock,
+
     /// Failed to create a migrations table.
     CreateMigrationsTable,
 
+    /// Failed to get a version of the migrations table.
+    GetMigrationsTableVersion,
+
+    /// Failed to upgrade a migrations table.
+    UpgradeMigrationsTable,
+
     /// Failed to apply SQL.
     ApplySql,
 
@@ -92,7 +215,13 @@ impl fmt::Display for DbKind {
             DbKind::OpenTransaction => fmt.write_str("Failed to open a transaction"),
             DbKind::CommitTransaction => fmt.write_str("Failed to commit a transaction"),
             DbKind::RollbackTransaction => fmt.write_str("Failed to rollback a transaction"),
+            DbKind::AcquireLock => fmt.write_str("Failed to acquire a migrations lock"),
+            DbKind::ReleaseLock => fmt.write_str("Failed to release a migrations lock"),
             DbKind::CreateMigrationsTable => fmt.write_str("Failed to create a migrations table"),
+            DbKind::GetMigrationsTableVersion => {
+                fmt.write_str("Failed to get a version of the migrations table")
+            }
+            DbKind::UpgradeMigrationsTable => fmt.write_str("Failed to upgrade a migrations table"),
             DbKind::ApplySql => fmt.write_str("Failed to apply sql"),
             DbKind::InsertMigration => fmt.write_str("Failed to insert a migration"),
             DbKind::DeleteMigration => fmt.write_str("Failed to delete a migration"),
@@ -106,11 +235,25 @@ impl fmt::Display for DbKind {
 pub struct DbError {
     kind: DbKind,
     origin: StdError,
+    migration: Option<String>,
+    details: Box<SqlErrorDetails>,
 }
 
 impl fmt::Display for DbError {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(fmt, "{} - {}", &self.kind, &self.origin)
+        write!(fmt, "{}", &self.kind)?;
+        if let Some(ref migration) = self.migration {
+            write!(fmt, r#" in "{migration}" migration"#)?;
+        }
+        if let Some(ref statement) = self.details.statement {
+            write!(
+                fmt,
+                " at line {} (statement {})",
+                statement.line(),
+                statement.index() + 1
+            )?;
+        }
+        write!(fmt, " - {}", &self.origin)
     }
 }
 
@@ -126,4 +269,265 @@ impl DbError {
     pub fn origin(&self) -> &StdError {
         &self.origin
     }
+
+    /// Returns name of the migration, in which the error occurred.
+    #[must_use]
+    pub fn migration(&self) -> Option<&str> {
+        self.migration.as_deref()
+    }
+
+    /// Returns the failed statement of the applied SQL if the client can detect it.
+    #[must_use]
+    pub fn statement(&self) -> Option<&FailedStatement> {
+        self.details.statement.as_ref()
+    }
+
+    /// Returns `SQLSTATE` code of the error.
+    #[must_use]
+    pub fn code(&self) -> Option<&str> {
+        self.details.code.as_deref()
+    }
+
+    /// Returns an optional secondary error message with more details.
+    #[must_use]
+    pub fn detail(&self) -> Option<&str> {
+        self.details.detail.as_deref()
+    }
+
+    /// Returns an optional suggestion what to do about the error.
+    #[must_use]
+    pub fn hint(&self) -> Option<&str> {
+        self.details.hint.as_deref()
+    }
+}
+
+/// Statement of the applied SQL that caused the error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailedStatement {
+    index: usize,
+    span: Range<usize>,
+    line: usize,
+    sql: String,
+    error_position: Option<usize>,
+}
+
+impl FailedStatement {
+    /// Creates a failed statement with the index of the statement and its byte range in
+    /// the applied SQL.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the span is out of bounds of the SQL or is not on char boundaries.
+    #[must_use]
+    pub fn new(sql: &str, index: usize, span: Range<usize>) -> Self {
+        FailedStatement {
+            index,
+            line: sql[..span.start].matches('\n').count() + 1,
+            sql: sql[span.clone()].to_owned(),
+            span,
+            error_position: None,
+        }
+    }
+
+    /// Sets the byte position of the error in the applied SQL, if the database reports it.
+    #[must_use]
+    pub fn with_error_position(mut self, position: usize) -> Self {
+        self.error_position = Some(position);
+        self
+    }
+
+    /// Returns zero-based index of the statement in the applied SQL.
+    #[must_use]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns byte range of the statement in the applied SQL.
+    #[must_use]
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Returns the line number where the statement starts in the migration source, e.g.
+    /// in the single migration file, or in the applied SQL if the source is unknown.
+    #[must_use]
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns SQL of the statement.
+    #[must_use]
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// Returns byte position of the error in the applied SQL.
+    #[must_use]
+    pub fn error_position(&self) -> Option<usize> {
+        self.error_position
+    }
+}
+
+#[derive(Debug, Default)]
+struct SqlErrorDetails {
+    statement: Option<FailedStatement>,
+    code: Option<String>,
+    detail: Option<String>,
+    hint: Option<String>,
+}
+
+/// Error of the executed SQL with additional details reported by the database.
+///
+/// Clients can return it from [`BatchExecute::batch_execute`] and all details are available
+/// in [`DbError`].
+///
+/// [`BatchExecute::batch_execute`]: crate::managers::BatchExecute::batch_execute
+#[derive(Debug)]
+pub struct SqlError {
+    origin: StdError,
+    details: SqlErrorDetails,
+}
+
+impl SqlError {
+    /// Creates an error of the executed SQL without details.
+    #[must_use]
+    pub fn new(origin: StdError) -> Self {
+        SqlError {
+            origin,
+            details: SqlErrorDetails::default(),
+        }
+    }
+
+    /// Sets the statement that caused the error.
+    #[must_use]
+    pub fn statement(mut self, statement: FailedStatement) -> Self {
+        self.details.statement = Some(statement);
+        self
+    }
+
+    /// Sets `SQLSTATE` code of the error.
+    #[must_use]
+    pub fn code<S: Into<String>>(mut self, code: S) -> Self {
+        self.details.code = Some(code.into());
+        self
+    }
+
+    /// Sets a secondary error message with more details.
+    #[must_use]
+    pub fn detail<S: Into<String>>(mut self, detail: S) -> Self {
+        self.details.detail = Some(detail.into());
+        self
+    }
+
+    /// Sets a suggestion what to do about the error.
+    #[must_use]
+    pub fn hint<S: Into<String>>(mut self, hint: S) -> Self {
+        self.details.hint = Some(hint.into());
+        self
+    }
+}
+
+impl fmt::Display for SqlError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "{}", &self.origin)
+    }
+}
+
+impl std::error::Error for SqlError {}
+
+/// Represents errors of migration versions, that don't allow to order migrations.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum VersionError {
+    /// Migration name doesn't start with a version.
+    Missing(String),
+
+    /// Several migrations have the same version.
+    Duplicate {
+        /// Duplicated version.
+        version: u64,
+        /// Names of migrations with the same version.
+        names: Vec<String>,
+    },
+}
+
+impl fmt::Display for VersionError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VersionError::Missing(ref name) => write!(
+                fmt,
+                r#"Cannot parse version of "{name}" migration. Migration name must start with a timestamp or number followed by "_""#
+            ),
+            VersionError::Duplicate { version, ref names } => write!(
+                fmt,
+                "Migrations {} have the same version {version}",
+                names.join(", ")
+            ),
+        }
+    }
+}
+
+/// Represents error of the migration run that failed after some migrations were committed or
+/// while a migration was running outside a transaction. The database is left in
+/// an intermediate state, that should be checked manually.
+#[derive(Debug)]
+pub struct PartialRunError {
+    failed: String,
+    committed: Vec<String>,
+    in_transaction: bool,
+    origin: Box<Error>,
+}
+
+impl fmt::Display for PartialRunError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self.origin {
+            // The database error already contains the migration name.
+            Error::Db(ref error) if error.migration().is_some() => write!(fmt, "{error}")?,
+            ref origin => write!(
+                fmt,
+                r#"Failed to run "{}" migration - {origin}"#,
+                &self.failed
+            )?,
+        }
+        if !self.in_transaction {
+            fmt.write_str(
+                ". The migration was running outside a transaction and may be applied partially",
+            )?;
+        }
+        if self.committed.is_empty() {
+            fmt.write_str(". No migrations were committed")
+        } else {
+            write!(
+                fmt,
+                ". Migrations {} were committed",
+                self.committed.join(", ")
+            )
+        }
+    }
+}
+
+impl PartialRunError {
+    /// Returns name of the failed migration.
+    #[must_use]
+    pub fn failed(&self) -> &str {
+        &self.failed
+    }
+
+    /// Returns names of migrations that were committed before the failure.
+    #[must_use]
+    pub fn committed(&self) -> &[String] {
+        &self.committed
+    }
+
+    /// Checks if the failed migration was running in a transaction that was rolled back.
+    #[must_use]
+    pub fn in_transaction(&self) -> bool {
+        self.in_transaction
+    }
+
+    /// Returns origin error of the failed migration.
+    #[must_use]
+    pub fn origin(&self) -> &Error {
+        &self.origin
+    }
 }
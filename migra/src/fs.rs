@@ -1,34 +1,177 @@
 use crate::errors::MigraResult;
 use crate::migration;
+use crate::migrator::{Direction, MigrationSource};
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-/// Checks if the directory is a migration according to the principles of the crate.
-#[must_use]
-pub fn is_migration_dir(path: &Path) -> bool {
-    path.join("up.sql").exists() && path.join("down.sql").exists()
-}
+pub use migra_parser::{is_migration_dir, is_migration_file, is_sql_file};
 
-/// Get all migration directories from path and returns as [List].
+/// Get all migration directories and single migration files from path and returns
+/// as [List].
+///
+/// This utility checks if the directory or the file is a migration. See
+/// [`is_migration_dir`] and [`is_migration_file`] for more information. Other `.sql` files
+/// are skipped with a warning.
+///
+/// Migrations are sorted by version. See [`List::sort_by_version`] for more information.
 ///
-/// This utility checks if the directory is a migration. See [`is_migration_dir`] for
-/// more information.
+/// Each migration in the list contains a checksum of the upgrade SQL content and a path to
+/// the migration directory or file. See [`Migration::from_dir`] and [`Migration::from_file`]
+/// for more information.
 ///
 /// [List]: migration::List
+/// [List::sort_by_version]: migration::List::sort_by_version
 /// [is_migration_dir]: fs::is_migration_dir
+/// [is_migration_file]: fs::is_migration_file
+/// [Migration::from_dir]: migration::Migration::from_dir
+/// [Migration::from_file]: migration::Migration::from_file
 pub fn get_all_migrations(dir_path: &Path) -> MigraResult<migration::List> {
-    let mut entries = match dir_path.read_dir() {
+    let entries = match dir_path.read_dir() {
         Err(e) if e.kind() == io::ErrorKind::NotFound => vec![],
         entries => entries?
             .filter_map(|res| res.ok().map(|e| e.path()))
-            .filter(|path| is_migration_dir(path))
+            .filter(|path| {
+                if is_migration_dir(path) || is_migration_file(path) {
+                    return true;
+                }
+                if is_sql_file(path) {
+                    tracing::warn!(
+                        path = %path.display(),
+                        "Skip SQL file without version or migration sections"
+                    );
+                }
+                false
+            })
             .collect::<Vec<_>>(),
     };
 
     if entries.is_empty() {
+        tracing::debug!(path = %dir_path.display(), "No migrations found");
         return Ok(migration::List::new());
     }
 
-    entries.sort();
-    Ok(migration::List::from(entries))
+    let mut migrations = entries
+        .iter()
+        .map(|path| read_migration(path))
+        .collect::<Result<migration::List, _>>()?;
+    migrations.sort_by_version()?;
+    tracing::debug!(
+        path = %dir_path.display(),
+        count = migrations.len(),
+        "Read migrations from directory"
+    );
+
+    Ok(migrations)
+}
+
+/// Reads migration with specific name from the migrations directory.
+pub fn get_migration(dir_path: &Path, name: &str) -> MigraResult<migration::Migration> {
+    let migration_dir_path = dir_path.join(name);
+    if is_migration_dir(&migration_dir_path) {
+        return migration::Migration::from_dir(&migration_dir_path);
+    }
+
+    let migration_file_path = dir_path.join(format!("{name}.sql"));
+    if is_migration_file(&migration_file_path) {
+        return migration::Migration::from_file(&migration_file_path);
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("Cannot find {name} migration in {}", dir_path.display()),
+    )
+    .into())
+}
+
+fn read_migration(path: &Path) -> MigraResult<migration::Migration> {
+    if is_migration_file(path) {
+        migration::Migration::from_file(path)
+    } else {
+        migration::Migration::from_dir(path)
+    }
+}
+
+/// Migrations directory as a migration source.
+impl MigrationSource for PathBuf {
+    fn all_migrations(&self) -> MigraResult<migration::List> {
+        get_all_migrations(self)
+    }
+
+    fn upgrade_sql(&self, migration: &migration::Migration) -> MigraResult<String> {
+        match migration.upgrade_sql()? {
+            Some(content) => Ok(content.to_owned()),
+            None => get_migration(self, migration.name())?
+                .upgrade_sql()
+                .map(|content| content.unwrap_or_default().to_owned()),
+        }
+    }
+
+    fn downgrade_sql(&self, migration: &migration::Migration) -> MigraResult<String> {
+        match migration.downgrade_sql()? {
+            Some(content) => Ok(content.to_owned()),
+            None => get_migration(self, migration.name())?
+                .downgrade_sql()
+                .map(|content| content.unwrap_or_default().to_owned()),
+        }
+    }
+
+    fn line_offset(
+        &self,
+        migration: &migration::Migration,
+        direction: Direction,
+    ) -> MigraResult<usize> {
+        let loaded_migration;
+        let migration = if migration.source().is_some() {
+            migration
+        } else {
+            loaded_migration = get_migration(self, migration.name())?;
+            &loaded_migration
+        };
+
+        Ok(match direction {
+            Direction::Upgrade => migration.upgrade_line_offset(),
+            Direction::Downgrade => migration.downgrade_line_offset(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn skip_sql_files_that_are_not_migrations() -> MigraResult<()> {
+        let dir_path = env::temp_dir().join(format!("migra_fs_{}", std::process::id()));
+        std::fs::create_dir_all(&dir_path)?;
+        std::fs::write(
+            dir_path.join("210218232851_create_articles.sql"),
+            "-- migrate:up\nSELECT 1;\n-- migrate:down\nSELECT 2;\n",
+        )?;
+        std::fs::write(dir_path.join("schema.sql"), "CREATE TABLE articles ();\n")?;
+
+        let migrations = get_all_migrations(&dir_path)?;
+        assert_eq!(
+            migrations,
+            migration::List::from(vec!["210218232851_create_articles"])
+        );
+
+        std::fs::remove_dir_all(dir_path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn return_error_on_migration_dir_without_downgrade() -> MigraResult<()> {
+        let dir_path = env::temp_dir().join(format!("migra_fs_down_{}", std::process::id()));
+        let migration_dir_path = dir_path.join("210218232851_create_articles");
+        std::fs::create_dir_all(&migration_dir_path)?;
+        std::fs::write(migration_dir_path.join("up.sql"), "SELECT 1;")?;
+
+        assert!(get_all_migrations(&dir_path).is_err());
+
+        std::fs::remove_dir_all(dir_path)?;
+
+        Ok(())
+    }
 }
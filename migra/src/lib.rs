@@ -61,6 +61,59 @@
 //! }
 //! ```
 //!
+//! ## Migrator
+//!
+//! **Note:** This example requires to enable `sqlite` feature.
+//!
+//! Migrator applies and rolls back migrations from a migrations directory or embedded
+//! migrations in the same way as [migra-cli](https://crates.io/crates/migra-cli) does.
+//!
+//! ```rust,no_run
+//! # #[cfg(feature = "sqlite")]
+//! # mod example {
+//! use migra::clients::{OpenDatabaseConnection, SqliteClient};
+//! use migra::migrator::Target;
+//! use migra::Migrator;
+//! use std::path::PathBuf;
+//!
+//! fn main() -> migra::Result<()> {
+//!     let mut client = SqliteClient::new("./tasks.db")?;
+//!     let migrations_dir = PathBuf::from("database/migrations");
+//!
+//!     let mut migrator = Migrator::new(&mut client, &migrations_dir);
+//!     let status = migrator.status()?;
+//!     println!("{} pending migrations", status.pending().len());
+//!
+//!     migrator.upgrade(&Target::All)?;
+//!
+//!     Ok(())
+//! }
+//! # }
+//! # fn main() {}
+//! ```
+//!
+//! ## Embedded migrations
+//!
+//! **Note:** This example requires to enable `embed` feature.
+//!
+//! Migrations from the directory can be embedded into the binary at compile time
+//! and applied with a single call.
+//!
+//! ```rust,ignore
+//! use migra::clients::{OpenDatabaseConnection, SqliteClient};
+//! use migra::embedded::EmbeddedMigrations;
+//!
+//! static MIGRATIONS: EmbeddedMigrations = migra::embed_migrations!("database/migrations");
+//!
+//! fn main() -> migra::Result<()> {
+//!     let mut client = SqliteClient::new("local.db")?;
+//!
+//!     MIGRATIONS.run_pending_migrations(&mut client)?;
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
 //! ### Supported databases
 //!
 //! | Database Client | Feature      |
@@ -69,6 +122,20 @@
 //! | `MySQL`         | mysql        |
 //! | `Sqlite`        | sqlite       |
 //!
+//! Async clients implement async counterparts of the managers, e.g. `AsyncManageMigrations`.
+//!
+//! | Async Database Client | Feature        |
+//! |-----------------------|----------------|
+//! | `AsyncPostgres`       | async-postgres |
+//! | `AsyncSqlite`         | async-sqlite   |
+//!
+//! `Postgres` clients connect with TLS if the `tls` feature is enabled. Usage of TLS is controlled
+//! by the `sslmode` parameter of the connection string, e.g. `postgres://localhost/db?sslmode=verify-full`.
+//!
+//! `MockClient` keeps applied migrations in memory and records executed SQL, so code that manages
+//! migrations can be tested without a database. It is enabled with the `testing` feature, e.g.
+//! `migra = { version = "1.0", features = ["testing"] }` in `[dev-dependencies]`.
+//!
 #![deny(missing_debug_implementations)]
 #![deny(missing_docs)]
 #![deny(clippy::all, clippy::pedantic)]
@@ -82,6 +149,9 @@ pub mod clients;
 /// Includes all types of errors that uses in the crate.
 pub mod errors;
 
+/// Includes migrations that are embedded into the binary at compile time.
+pub mod embedded;
+
 /// Includes utilities that use the file system to work.
 pub mod fs;
 
@@ -93,5 +163,22 @@ pub mod managers;
 /// lists, that are used in managers and fs utils.
 pub mod migration;
 
+/// Includes a high-level migrator that applies and rolls back migrations
+/// from a migration source.
+pub mod migrator;
+
+/// Includes placeholders that are substituted in migration SQL.
+pub mod placeholders;
+
 pub use errors::{Error, MigraResult as Result, StdResult};
 pub use migration::{List as MigrationList, Migration};
+pub use migrator::Migrator;
+
+/// Embeds all migrations from the directory into the binary at compile time.
+/// Requires to enable `embed` feature.
+///
+/// See [`EmbeddedMigrations`] for more information.
+///
+/// [EmbeddedMigrations]: embedded::EmbeddedMigrations
+#[cfg(feature = "embed")]
+pub use migra_macros::embed_migrations;
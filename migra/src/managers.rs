@@ -1,5 +1,6 @@
 use crate::errors::{DbKind, Error, MigraResult, StdResult};
 use crate::migration;
+use std::time::{Duration, Instant};
 
 /// Used to execute SQL.
 ///
@@ -13,52 +14,184 @@ pub trait BatchExecute {
 pub trait ManageTransaction: BatchExecute {
     /// Opens transaction in database connection.
     fn begin_transaction(&mut self) -> MigraResult<()> {
+        tracing::debug!("BEGIN");
         self.batch_execute("BEGIN")
             .map_err(|err| Error::db(err, DbKind::OpenTransaction))
     }
 
     /// Cancels (Rollbacks) transaction in database connection.
     fn rollback_transaction(&mut self) -> MigraResult<()> {
+        tracing::debug!("ROLLBACK");
         self.batch_execute("ROLLBACK")
             .map_err(|err| Error::db(err, DbKind::RollbackTransaction))
     }
 
     /// Apply (Commit) transaction in database connection.
     fn commit_transaction(&mut self) -> MigraResult<()> {
+        tracing::debug!("COMMIT");
         self.batch_execute("COMMIT")
             .map_err(|err| Error::db(err, DbKind::CommitTransaction))
     }
 }
 
+/// Defines how migrations of one run are wrapped in transactions.
+///
+/// Migrations with the [`NO_TRANSACTION_DIRECTIVE`] always run outside a transaction.
+///
+/// [`NO_TRANSACTION_DIRECTIVE`]: migration::NO_TRANSACTION_DIRECTIVE
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TransactionStrategy {
+    /// All migrations are applied in a single transaction. If any migration fails, none of
+    /// them is applied.
+    #[default]
+    Single,
+
+    /// Each migration is applied in its own transaction. If any migration fails, previous
+    /// migrations stay applied.
+    PerMigration,
+
+    /// Migrations are applied without transactions. If any migration fails, it may be
+    /// applied partially.
+    None,
+}
+
+/// Used to prevent concurrent runs of migrations from different connections.
+pub trait ManageLock {
+    /// Acquires an exclusive lock for the migrations table. If the lock is held by another
+    /// connection, waits for it no longer than the specific timeout.
+    fn acquire_lock(&mut self, timeout: Duration) -> MigraResult<()>;
+
+    /// Releases the lock acquired with [`acquire_lock`].
+    ///
+    /// [acquire_lock]: ManageLock::acquire_lock
+    fn release_lock(&mut self) -> MigraResult<()>;
+}
+
+/// Version of the migrations table schema that is supported by the crate.
+///
+/// Each version adds changes to the previous one:
+///
+/// 1. The initial table with `id` and `name` columns.
+/// 2. Adds `checksum` column.
+/// 3. Adds `applied_at`, `duration_ms` and `applied_by` columns.
+/// 4. Recreates the `Sqlite` migrations table with autoincremented `id` column to
+///    keep the order of applied migrations.
+pub const MIGRATIONS_TABLE_VERSION: u32 = 4;
+
 /// Used to manage migrations in the database connection.
 pub trait ManageMigrations: BatchExecute {
     /// Applies SQL. Similar to [`BatchExecute`], but returns migra [Error].
     ///
     /// [BatchExecute]: managers::BatchExecute
     fn apply_sql(&mut self, sql: &str) -> MigraResult<()> {
+        tracing::debug!(len = sql.len(), "Applying SQL");
         self.batch_execute(sql)
             .map_err(|err| Error::db(err, DbKind::ApplySql))
     }
 
-    /// Creates migration table.
-    fn create_migrations_table(&mut self) -> MigraResult<()>;
+    /// Creates migration table or upgrades the existing one to the latest version in place.
+    /// Existing rows are kept.
+    ///
+    /// See [`MIGRATIONS_TABLE_VERSION`] for more information.
+    fn create_migrations_table(&mut self) -> MigraResult<()> {
+        let current_version = self.get_migrations_table_version()?;
+        if current_version < MIGRATIONS_TABLE_VERSION {
+            tracing::info!(
+                from = current_version,
+                to = MIGRATIONS_TABLE_VERSION,
+                "Upgrading migrations table"
+            );
+        }
 
-    /// Inserts new migration to table.
-    fn insert_migration(&mut self, name: &str) -> MigraResult<u64>;
+        for version in (current_version + 1)..=MIGRATIONS_TABLE_VERSION {
+            tracing::debug!(version, "Upgrading migrations table to version");
+            self.upgrade_migrations_table(version)?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the version of the migrations table schema.
+    ///
+    /// Returns `0` if the migrations table doesn't exist and `1` if the migrations table
+    /// was created before the schema versioning was introduced.
+    fn get_migrations_table_version(&mut self) -> MigraResult<u32>;
+
+    /// Upgrades the migrations table schema from the previous version to the specific
+    /// version and stores the new version. Unknown versions are ignored.
+    fn upgrade_migrations_table(&mut self, version: u32) -> MigraResult<()>;
+
+    /// Inserts new migration to table with checksum of the applied SQL, duration of the SQL
+    /// execution and `user@host` who applied the migration. The time when the migration was
+    /// applied is filled by the database.
+    ///
+    /// See [`calculate_checksum`] and [`applied_by`] for more information.
+    ///
+    /// [calculate_checksum]: migration::calculate_checksum
+    /// [applied_by]: migration::applied_by
+    fn insert_migration(
+        &mut self,
+        name: &str,
+        checksum: &str,
+        duration: Duration,
+        applied_by: &str,
+    ) -> MigraResult<u64>;
 
     /// Deletes migration from table.
     fn delete_migration(&mut self, name: &str) -> MigraResult<u64>;
 
-    /// Get applied migrations from table.
-    fn get_applied_migrations(&mut self) -> MigraResult<migration::List>;
+    /// Get applied migrations with their checksums and metadata from table.
+    ///
+    /// The latest applied migration is the first in the list. Returns an empty history if
+    /// the table doesn't exist. Checksums and metadata are unknown if the table was created
+    /// by an older version of the crate and isn't upgraded yet.
+    fn get_migrations_history(&mut self) -> MigraResult<Vec<migration::AppliedMigration>>;
+
+    /// Get applied migrations with their checksums from table.
+    ///
+    /// The latest applied migration is the first in the list.
+    fn get_applied_migrations(&mut self) -> MigraResult<migration::List> {
+        self.get_migrations_history().map(|history| {
+            history
+                .iter()
+                .map(migration::AppliedMigration::migration)
+                .collect()
+        })
+    }
 
     /// Applies SQL to upgrade database schema and inserts new migration to table.
     ///
     /// **Note:** Must be run in a transaction otherwise if the migration causes any
     /// error the data in the database may be inconsistent.
     fn run_upgrade_migration(&mut self, name: &str, content: &str) -> MigraResult<()> {
-        self.apply_sql(content)?;
-        self.insert_migration(name)?;
+        self.run_upgrade_migration_with_checksum(
+            name,
+            content,
+            &migration::calculate_checksum(content),
+        )
+    }
+
+    /// Applies SQL to upgrade database schema and inserts new migration to table with
+    /// the specific checksum, e.g. of the SQL content before placeholders were substituted.
+    ///
+    /// **Note:** Must be run in a transaction otherwise if the migration causes any
+    /// error the data in the database may be inconsistent.
+    fn run_upgrade_migration_with_checksum(
+        &mut self,
+        name: &str,
+        sql: &str,
+        checksum: &str,
+    ) -> MigraResult<()> {
+        let started_at = Instant::now();
+        self.apply_sql(sql)
+            .map_err(|err| err.with_migration(name))?;
+        self.insert_migration(
+            name,
+            checksum,
+            started_at.elapsed(),
+            &migration::applied_by(),
+        )?;
+        tracing::debug!(migration = name, "Inserted migration to migrations table");
         Ok(())
     }
 
@@ -67,8 +200,201 @@ pub trait ManageMigrations: BatchExecute {
     /// **Note:** Must be run in a transaction otherwise if the migration causes any
     /// error the data in the database may be inconsistent.
     fn run_downgrade_migration(&mut self, name: &str, content: &str) -> MigraResult<()> {
-        self.apply_sql(content)?;
+        self.apply_sql(content)
+            .map_err(|err| err.with_migration(name))?;
         self.delete_migration(name)?;
+        tracing::debug!(migration = name, "Deleted migration from migrations table");
+        Ok(())
+    }
+}
+
+/// Used to render SQL that changes the migrations table with inlined values instead of
+/// executing it, e.g. to show the script of a dry run.
+pub trait RenderMigrationsSql {
+    /// Returns SQL that [`ManageMigrations::upgrade_migrations_table`] executes for
+    /// the specific version, or `None` if the version is unknown.
+    fn upgrade_migrations_table_sql(&self, version: u32) -> Option<String>;
+
+    /// Returns SQL that [`ManageMigrations::insert_migration`] executes.
+    fn insert_migration_sql(
+        &self,
+        name: &str,
+        checksum: &str,
+        duration: Duration,
+        applied_by: &str,
+    ) -> String;
+
+    /// Returns SQL that [`ManageMigrations::delete_migration`] executes.
+    fn delete_migration_sql(&self, name: &str) -> String;
+}
+
+/// Async counterpart of [`BatchExecute`].
+///
+/// **Note:** Requires enabling `async` feature.
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+pub trait AsyncBatchExecute: Send {
+    /// Executes sql via original database client
+    async fn batch_execute(&mut self, sql: &str) -> StdResult<()>;
+}
+
+/// Async counterpart of [`ManageTransaction`].
+///
+/// **Note:** Requires enabling `async` feature.
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+pub trait AsyncManageTransaction: AsyncBatchExecute {
+    /// Opens transaction in database connection.
+    async fn begin_transaction(&mut self) -> MigraResult<()> {
+        tracing::debug!("BEGIN");
+        self.batch_execute("BEGIN")
+            .await
+            .map_err(|err| Error::db(err, DbKind::OpenTransaction))
+    }
+
+    /// Cancels (Rollbacks) transaction in database connection.
+    async fn rollback_transaction(&mut self) -> MigraResult<()> {
+        tracing::debug!("ROLLBACK");
+        self.batch_execute("ROLLBACK")
+            .await
+            .map_err(|err| Error::db(err, DbKind::RollbackTransaction))
+    }
+
+    /// Apply (Commit) transaction in database connection.
+    async fn commit_transaction(&mut self) -> MigraResult<()> {
+        tracing::debug!("COMMIT");
+        self.batch_execute("COMMIT")
+            .await
+            .map_err(|err| Error::db(err, DbKind::CommitTransaction))
+    }
+}
+
+/// Async counterpart of [`ManageMigrations`].
+///
+/// **Note:** Requires enabling `async` feature.
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+pub trait AsyncManageMigrations: AsyncBatchExecute {
+    /// Applies SQL. Similar to [`AsyncBatchExecute`], but returns migra [Error].
+    async fn apply_sql(&mut self, sql: &str) -> MigraResult<()> {
+        tracing::debug!(len = sql.len(), "Applying SQL");
+        self.batch_execute(sql)
+            .await
+            .map_err(|err| Error::db(err, DbKind::ApplySql))
+    }
+
+    /// Creates migration table or upgrades the existing one to the latest version in place.
+    /// Existing rows are kept.
+    ///
+    /// See [`MIGRATIONS_TABLE_VERSION`] for more information.
+    async fn create_migrations_table(&mut self) -> MigraResult<()> {
+        let current_version = self.get_migrations_table_version().await?;
+        if current_version < MIGRATIONS_TABLE_VERSION {
+            tracing::info!(
+                from = current_version,
+                to = MIGRATIONS_TABLE_VERSION,
+                "Upgrading migrations table"
+            );
+        }
+
+        for version in (current_version + 1)..=MIGRATIONS_TABLE_VERSION {
+            tracing::debug!(version, "Upgrading migrations table to version");
+            self.upgrade_migrations_table(version).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the version of the migrations table schema.
+    ///
+    /// See [`ManageMigrations::get_migrations_table_version`] for more information.
+    async fn get_migrations_table_version(&mut self) -> MigraResult<u32>;
+
+    /// Upgrades the migrations table schema from the previous version to the specific
+    /// version and stores the new version. Unknown versions are ignored.
+    async fn upgrade_migrations_table(&mut self, version: u32) -> MigraResult<()>;
+
+    /// Inserts new migration to table.
+    ///
+    /// See [`ManageMigrations::insert_migration`] for more information.
+    async fn insert_migration(
+        &mut self,
+        name: &str,
+        checksum: &str,
+        duration: Duration,
+        applied_by: &str,
+    ) -> MigraResult<u64>;
+
+    /// Deletes migration from table.
+    async fn delete_migration(&mut self, name: &str) -> MigraResult<u64>;
+
+    /// Get applied migrations with their checksums and metadata from table.
+    ///
+    /// The latest applied migration is the first in the list. Returns an empty history if
+    /// the table doesn't exist. Checksums and metadata are unknown if the table was created
+    /// by an older version of the crate and isn't upgraded yet.
+    async fn get_migrations_history(&mut self) -> MigraResult<Vec<migration::AppliedMigration>>;
+
+    /// Get applied migrations with their checksums from table.
+    ///
+    /// The latest applied migration is the first in the list.
+    async fn get_applied_migrations(&mut self) -> MigraResult<migration::List> {
+        self.get_migrations_history().await.map(|history| {
+            history
+                .iter()
+                .map(migration::AppliedMigration::migration)
+                .collect()
+        })
+    }
+
+    /// Applies SQL to upgrade database schema and inserts new migration to table.
+    ///
+    /// **Note:** Must be run in a transaction otherwise if the migration causes any
+    /// error the data in the database may be inconsistent.
+    async fn run_upgrade_migration(&mut self, name: &str, content: &str) -> MigraResult<()> {
+        self.run_upgrade_migration_with_checksum(
+            name,
+            content,
+            &migration::calculate_checksum(content),
+        )
+        .await
+    }
+
+    /// Applies SQL to upgrade database schema and inserts new migration to table with
+    /// the specific checksum.
+    ///
+    /// See [`ManageMigrations::run_upgrade_migration_with_checksum`] for more information.
+    async fn run_upgrade_migration_with_checksum(
+        &mut self,
+        name: &str,
+        sql: &str,
+        checksum: &str,
+    ) -> MigraResult<()> {
+        let started_at = Instant::now();
+        self.apply_sql(sql)
+            .await
+            .map_err(|err| err.with_migration(name))?;
+        self.insert_migration(
+            name,
+            checksum,
+            started_at.elapsed(),
+            &migration::applied_by(),
+        )
+        .await?;
+        tracing::debug!(migration = name, "Inserted migration to migrations table");
+        Ok(())
+    }
+
+    /// Applies SQL to downgrade database schema and deletes migration from table.
+    ///
+    /// **Note:** Must be run in a transaction otherwise if the migration causes any
+    /// error the data in the database may be inconsistent.
+    async fn run_downgrade_migration(&mut self, name: &str, content: &str) -> MigraResult<()> {
+        self.apply_sql(content)
+            .await
+            .map_err(|err| err.with_migration(name))?;
+        self.delete_migration(name).await?;
+        tracing::debug!(migration = name, "Deleted migration from migrations table");
         Ok(())
     }
 }
@@ -1,19 +1,221 @@
-use crate::errors::MigraResult;
+use crate::errors::{Error, MigraResult, VersionError};
+#[cfg(feature = "async")]
+use crate::managers::AsyncManageMigrations;
 use crate::managers::ManageMigrations;
+use sha2::{Digest, Sha256};
+use std::env;
+use std::io;
 use std::iter::FromIterator;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
 
-/// A simple wrap over string.
-#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Calculates a checksum of the migration SQL content.
+///
+/// The checksum is a hex encoded `SHA-256` digest, which is stored in the migrations
+/// table to detect changes in already applied migrations.
+///
+/// # Example
+///
+/// ```rust
+/// # use migra::migration::calculate_checksum;
+/// assert_eq!(
+///     calculate_checksum("SELECT 1;"),
+///     calculate_checksum(&String::from("SELECT 1;"))
+/// );
+/// assert_ne!(calculate_checksum("SELECT 1;"), calculate_checksum("SELECT 2;"));
+/// ```
+#[must_use]
+pub fn calculate_checksum(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+/// Returns the `user@host` of the current process, which is stored in the migrations table
+/// as the author of applied migrations.
+///
+/// User and host are read from the `USER` (`USERNAME`) and `HOSTNAME` (`COMPUTERNAME`)
+/// environment variables. The host falls back to the content of `/etc/hostname`.
+#[must_use]
+pub fn applied_by() -> String {
+    let user = env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .unwrap_or_else(|_| String::from("unknown"));
+    let host = env::var("HOSTNAME")
+        .or_else(|_| env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| {
+            std::fs::read_to_string("/etc/hostname")
+                .ok()
+                .map(|hostname| hostname.trim().to_owned())
+        })
+        .filter(|hostname| !hostname.is_empty())
+        .unwrap_or_else(|| String::from("unknown"));
+
+    format!("{user}@{host}")
+}
+
+/// Migration with a name and optional metadata.
+///
+/// The name is usually prefixed with a version (timestamp or sequential number), e.g.
+/// `210218232851_create_articles`, so the version and a human description are parsed
+/// from the name.
+///
+/// Migrations that were read from the migrations directory also know their source location
+/// and load SQL content lazily from it.
+///
+/// Migrations with the [`IRREVERSIBLE_DIRECTIVE`] in the upgrade SQL content cannot be
+/// rolled back and don't need the downgrade SQL content.
+///
+/// Migrations are equal if they have the same name. Use [`Migration::is_drifted_from`] to
+/// compare checksums.
+#[derive(Debug, Clone, Default)]
 pub struct Migration {
     name: String,
+    version: Option<u64>,
+    description: Option<String>,
+    checksum: Option<String>,
+    source: Option<PathBuf>,
+    irreversible: bool,
+    upgrade_sql: OnceLock<String>,
+    downgrade_sql: OnceLock<String>,
+    line_offsets: (usize, usize),
 }
 
+impl PartialEq for Migration {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for Migration {}
+
 impl Migration {
     /// Creates new migration by name.
     #[must_use]
     pub fn new(name: &str) -> Self {
+        let (version, description) = parse_name(name);
+
         Migration {
             name: name.to_owned(),
+            version,
+            description,
+            ..Migration::default()
+        }
+    }
+
+    /// Creates new migration by name with checksum of the upgrade SQL content.
+    ///
+    /// See [`calculate_checksum`] for more information.
+    #[must_use]
+    pub fn with_checksum(name: &str, checksum: &str) -> Self {
+        Migration {
+            checksum: Some(checksum.to_owned()),
+            ..Migration::new(name)
+        }
+    }
+
+    /// Creates new migration by name with upgrade and downgrade SQL content.
+    /// The checksum is calculated from the upgrade SQL content.
+    ///
+    /// The downgrade SQL content of irreversible migrations is usually empty.
+    #[must_use]
+    pub fn with_sql(name: &str, upgrade_sql: &str, downgrade_sql: &str) -> Self {
+        let migration = Migration {
+            irreversible: has_directive(upgrade_sql, IRREVERSIBLE_DIRECTIVE),
+            ..Migration::with_checksum(name, &calculate_checksum(upgrade_sql))
+        };
+        migration.upgrade_sql.get_or_init(|| upgrade_sql.to_owned());
+        migration
+            .downgrade_sql
+            .get_or_init(|| downgrade_sql.to_owned());
+        migration
+    }
+
+    /// Creates new migration from the migration directory with `up.sql` and `down.sql`
+    /// files. The name is a directory name. Irreversible migrations have no `down.sql` file.
+    ///
+    /// The upgrade SQL content is read immediately to calculate the checksum, the downgrade
+    /// SQL content is read on the first access.
+    ///
+    /// Returns an error with [`io::ErrorKind::InvalidData`] kind if the directory name is not
+    /// valid UTF-8 or the directory has no `down.sql` file, but the migration isn't marked
+    /// with [`IRREVERSIBLE_DIRECTIVE`].
+    pub fn from_dir(path: &Path) -> MigraResult<Self> {
+        let name = read_name(path, path.file_name())?;
+        let upgrade_sql = std::fs::read_to_string(path.join("up.sql"))?;
+        let irreversible = has_directive(&upgrade_sql, IRREVERSIBLE_DIRECTIVE);
+        if !irreversible && !path.join("down.sql").exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Migration directory {} must contain down.sql file or {IRREVERSIBLE_DIRECTIVE:?} directive",
+                    path.display()
+                ),
+            )
+            .into());
+        }
+
+        let migration = Migration {
+            source: Some(path.to_path_buf()),
+            irreversible,
+            ..Migration::with_checksum(name, &calculate_checksum(&upgrade_sql))
+        };
+        migration.upgrade_sql.get_or_init(|| upgrade_sql);
+        Ok(migration)
+    }
+
+    /// Creates new migration from the single migration file with `-- migrate:up` and
+    /// `-- migrate:down` sections. The name is a file name without extension.
+    /// Irreversible migrations have no `-- migrate:down` section.
+    ///
+    /// See [`parse_sections`] for more information.
+    ///
+    /// Returns an error with [`io::ErrorKind::InvalidData`] kind if the file name is not
+    /// valid UTF-8.
+    pub fn from_file(path: &Path) -> MigraResult<Self> {
+        let name = read_name(path, path.file_stem())?;
+        let content = std::fs::read_to_string(path)?;
+        let Some((upgrade_sql, downgrade_sql)) = parse_migration_file(&content) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Migration file {} must contain {UP_SECTION_MARKER:?} and {DOWN_SECTION_MARKER:?} sections or {IRREVERSIBLE_DIRECTIVE:?} directive",
+                    path.display()
+                ),
+            )
+            .into());
+        };
+
+        Ok(Migration {
+            source: Some(path.to_path_buf()),
+            ..Migration::with_sql(name, upgrade_sql, downgrade_sql).with_line_offsets(
+                section_line_offset(&content, upgrade_sql),
+                section_line_offset(&content, downgrade_sql),
+            )
+        })
+    }
+
+    /// Sets numbers of lines before the upgrade and downgrade SQL content in the migration
+    /// source, e.g. before sections of the single migration file.
+    ///
+    /// They are added to line numbers of failed statements, so errors point to lines of
+    /// the migration source.
+    #[must_use]
+    pub fn with_line_offsets(self, upgrade: usize, downgrade: usize) -> Self {
+        Migration {
+            line_offsets: (upgrade, downgrade),
+            ..self
+        }
+    }
+
+    /// Marks the migration as irreversible.
+    ///
+    /// See [`IRREVERSIBLE_DIRECTIVE`] for more information.
+    #[must_use]
+    pub fn irreversible(self) -> Self {
+        Migration {
+            irreversible: true,
+            ..self
         }
     }
 
@@ -22,6 +224,204 @@ impl Migration {
     pub fn name(&self) -> &String {
         &self.name
     }
+
+    /// Returns version parsed from the numeric prefix of the name.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use migra::Migration;
+    /// assert_eq!(Migration::new("210218232851_create_articles").version(), Some(210218232851));
+    /// assert_eq!(Migration::new("initial_migration").version(), None);
+    /// ```
+    #[must_use]
+    pub fn version(&self) -> Option<u64> {
+        self.version
+    }
+
+    /// Returns human description parsed from the name without version.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use migra::Migration;
+    /// assert_eq!(
+    ///     Migration::new("210218232851_create_articles").description(),
+    ///     Some("create articles")
+    /// );
+    /// ```
+    #[must_use]
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Returns checksum of the upgrade SQL content if it is known.
+    ///
+    /// Migrations that were applied before checksums were introduced have no checksum.
+    #[must_use]
+    pub fn checksum(&self) -> Option<&str> {
+        self.checksum.as_deref()
+    }
+
+    /// Checks if the migration cannot be rolled back.
+    ///
+    /// See [`IRREVERSIBLE_DIRECTIVE`] for more information.
+    #[must_use]
+    pub fn is_irreversible(&self) -> bool {
+        self.irreversible
+    }
+
+    /// Returns path to the migration directory or the single migration file if the migration
+    /// was read from it.
+    #[must_use]
+    pub fn source(&self) -> Option<&Path> {
+        self.source.as_deref()
+    }
+
+    /// Returns the number of lines before the upgrade SQL content in the migration source.
+    ///
+    /// See [`Migration::with_line_offsets`] for more information.
+    #[must_use]
+    pub fn upgrade_line_offset(&self) -> usize {
+        self.line_offsets.0
+    }
+
+    /// Returns the number of lines before the downgrade SQL content in the migration source.
+    ///
+    /// See [`Migration::with_line_offsets`] for more information.
+    #[must_use]
+    pub fn downgrade_line_offset(&self) -> usize {
+        self.line_offsets.1
+    }
+
+    /// Returns SQL content to upgrade database schema.
+    ///
+    /// Returns `None` if the migration has neither SQL content nor a source location,
+    /// e.g. it was read from the migrations table.
+    pub fn upgrade_sql(&self) -> MigraResult<Option<&str>> {
+        self.load_sql(&self.upgrade_sql, "up.sql")
+    }
+
+    /// Returns SQL content to downgrade database schema.
+    ///
+    /// Returns `None` if the migration has neither SQL content nor a source location,
+    /// e.g. it was read from the migrations table.
+    pub fn downgrade_sql(&self) -> MigraResult<Option<&str>> {
+        self.load_sql(&self.downgrade_sql, "down.sql")
+    }
+
+    fn load_sql<'a>(
+        &'a self,
+        content: &'a OnceLock<String>,
+        file_name: &str,
+    ) -> MigraResult<Option<&'a str>> {
+        if let Some(content) = content.get() {
+            return Ok(Some(content));
+        }
+
+        match self.source {
+            Some(ref source) => {
+                let loaded_content = std::fs::read_to_string(source.join(file_name))?;
+                Ok(Some(content.get_or_init(|| loaded_content)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Checks if both migrations have the same name, but different known checksums.
+    #[must_use]
+    pub fn is_drifted_from(&self, other: &Migration) -> bool {
+        self.name == other.name
+            && matches!(
+                (self.checksum(), other.checksum()),
+                (Some(checksum), Some(other_checksum)) if checksum != other_checksum
+            )
+    }
+}
+
+pub use migra_parser::{
+    has_directive, parse_migration_file, parse_sections, parse_version, section_line_offset,
+    DOWN_SECTION_MARKER, IRREVERSIBLE_DIRECTIVE, NO_TRANSACTION_DIRECTIVE, UP_SECTION_MARKER,
+};
+
+/// Reads the migration name from the file name or stem of the migration source.
+fn read_name<'a>(path: &Path, name: Option<&'a std::ffi::OsStr>) -> MigraResult<&'a str> {
+    name.and_then(std::ffi::OsStr::to_str).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Cannot read migration name from {}", path.display()),
+        )
+        .into()
+    })
+}
+
+fn parse_name(name: &str) -> (Option<u64>, Option<String>) {
+    let version = parse_version(name);
+    let description = match version {
+        Some(_) => name.split_once('_').map_or("", |(_, rest)| rest),
+        None => name,
+    }
+    .replace('_', " ");
+
+    (
+        version,
+        Some(description).filter(|description| !description.is_empty()),
+    )
+}
+
+/// Applied migration with metadata from the migrations table.
+///
+/// Migrations that were applied before metadata was introduced have no metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedMigration {
+    migration: Migration,
+    applied_at: Option<SystemTime>,
+    duration: Option<Duration>,
+    applied_by: Option<String>,
+}
+
+impl AppliedMigration {
+    /// Creates new applied migration with metadata.
+    #[must_use]
+    pub fn new(
+        migration: Migration,
+        applied_at: Option<SystemTime>,
+        duration: Option<Duration>,
+        applied_by: Option<String>,
+    ) -> Self {
+        AppliedMigration {
+            migration,
+            applied_at,
+            duration,
+            applied_by,
+        }
+    }
+
+    /// Returns applied migration.
+    #[must_use]
+    pub fn migration(&self) -> &Migration {
+        &self.migration
+    }
+
+    /// Returns time when the migration was applied.
+    #[must_use]
+    pub fn applied_at(&self) -> Option<SystemTime> {
+        self.applied_at
+    }
+
+    /// Returns how long the migration SQL was executed.
+    #[must_use]
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    /// Returns `user@host` that applied the migration.
+    ///
+    /// See [`applied_by`] for more information.
+    #[must_use]
+    pub fn applied_by(&self) -> Option<&str> {
+        self.applied_by.as_deref()
+    }
 }
 
 /// Wrap over migration vector. Can be implicitly converted to a vector and has
@@ -123,6 +523,37 @@ impl List {
         self.inner.push(Migration::new(name));
     }
 
+    /// Sorts migrations by version. See [`parse_version`] for more information.
+    ///
+    /// Returns an error if any migration has no version or several migrations have the
+    /// same version, because the order of such migrations is ambiguous.
+    pub fn sort_by_version(&mut self) -> MigraResult<()> {
+        if let Some(migration) = self.inner.iter().find(|m| m.version().is_none()) {
+            return Err(Error::Version(VersionError::Missing(
+                migration.name.clone(),
+            )));
+        }
+
+        self.inner.sort_by_key(Migration::version);
+
+        if let Some(duplicates) = self
+            .inner
+            .windows(2)
+            .find(|pair| pair[0].version() == pair[1].version())
+        {
+            let version = duplicates[0].version().unwrap_or_default();
+            let names = self
+                .inner
+                .iter()
+                .filter(|migration| migration.version() == Some(version))
+                .map(|migration| migration.name.clone())
+                .collect();
+            return Err(Error::Version(VersionError::Duplicate { version, names }));
+        }
+
+        Ok(())
+    }
+
     /// Check if list contains specific migration.
     #[must_use]
     pub fn contains(&self, other_migration: &Migration) -> bool {
@@ -146,6 +577,22 @@ impl List {
             .collect()
     }
 
+    /// Returns migrations from the current list that have another checksum in the specific
+    /// list. Usually the current list contains all migrations and the specific list contains
+    /// applied migrations, so the result is a list of applied migrations that were changed.
+    ///
+    /// Migrations without checksum are ignored.
+    #[must_use]
+    pub fn drifted(&self, list: &List) -> List {
+        self.inner
+            .iter()
+            .filter(|migration| {
+                list.iter()
+                    .any(|other_migration| migration.is_drifted_from(other_migration))
+            })
+            .collect()
+    }
+
     /// Runs a upgrade migration with SQL content and adds a new migration to the current list
     /// If there is no migration migration with specific name in the list.
     pub fn should_run_upgrade_migration(
@@ -172,7 +619,10 @@ impl List {
         name: &str,
         content: &str,
     ) -> MigraResult<bool> {
-        let is_latest = self.inner.last() == Some(&Migration::new(name));
+        let is_latest = self
+            .inner
+            .last()
+            .is_some_and(|migration| migration.name() == name);
 
         if is_latest {
             client.run_downgrade_migration(name, content)?;
@@ -181,6 +631,49 @@ impl List {
 
         Ok(is_latest)
     }
+
+    /// Async counterpart of [`List::should_run_upgrade_migration`].
+    ///
+    /// **Note:** Requires enabling `async` feature.
+    #[cfg(feature = "async")]
+    pub async fn should_run_upgrade_migration_async(
+        &mut self,
+        client: &mut dyn AsyncManageMigrations,
+        name: &str,
+        content: &str,
+    ) -> MigraResult<bool> {
+        let is_missed = !self.contains_name(name);
+
+        if is_missed {
+            client.run_upgrade_migration(name, content).await?;
+            self.push_name(name);
+        }
+
+        Ok(is_missed)
+    }
+
+    /// Async counterpart of [`List::should_run_downgrade_migration`].
+    ///
+    /// **Note:** Requires enabling `async` feature.
+    #[cfg(feature = "async")]
+    pub async fn should_run_downgrade_migration_async(
+        &mut self,
+        client: &mut dyn AsyncManageMigrations,
+        name: &str,
+        content: &str,
+    ) -> MigraResult<bool> {
+        let is_latest = self
+            .inner
+            .last()
+            .is_some_and(|migration| migration.name() == name);
+
+        if is_latest {
+            client.run_downgrade_migration(name, content).await?;
+            self.inner.pop();
+        }
+
+        Ok(is_latest)
+    }
 }
 
 #[cfg(test)]
@@ -236,4 +729,216 @@ mod tests {
 
         assert_eq!(excluded, List::from(vec![SECOND_MIGRATION]));
     }
+
+    #[test]
+    fn create_drifted_migration_list() {
+        let all_migrations = List::from(vec![
+            Migration::with_checksum(FIRST_MIGRATION, &calculate_checksum("SELECT 2;")),
+            Migration::with_checksum(SECOND_MIGRATION, &calculate_checksum("SELECT 3;")),
+        ]);
+        let applied_migrations = List::from(vec![
+            Migration::with_checksum(FIRST_MIGRATION, &calculate_checksum("SELECT 1;")),
+            Migration::new(SECOND_MIGRATION),
+        ]);
+        let drifted = all_migrations.drifted(&applied_migrations);
+
+        assert_eq!(drifted.len(), 1);
+        assert_eq!(drifted[0].name(), FIRST_MIGRATION);
+    }
+
+    #[test]
+    fn ignore_migrations_with_same_checksum() {
+        let checksum = calculate_checksum("SELECT 1;");
+        let all_migrations = List::from(vec![Migration::with_checksum(FIRST_MIGRATION, &checksum)]);
+        let applied_migrations =
+            List::from(vec![Migration::with_checksum(FIRST_MIGRATION, &checksum)]);
+
+        assert!(all_migrations.drifted(&applied_migrations).is_empty());
+    }
+
+    #[test]
+    fn parse_version_and_description_from_name() {
+        let migration = Migration::new("210218232851_create_articles");
+        assert_eq!(migration.version(), Some(210_218_232_851));
+        assert_eq!(migration.description(), Some("create articles"));
+
+        let migration = Migration::new("10");
+        assert_eq!(migration.version(), Some(10));
+        assert_eq!(migration.description(), None);
+
+        let migration = Migration::new(FIRST_MIGRATION);
+        assert_eq!(migration.version(), None);
+        assert_eq!(migration.description(), Some("initial migration"));
+    }
+
+    #[test]
+    fn compare_migrations_by_name() {
+        let migration = Migration::with_sql(FIRST_MIGRATION, "SELECT 1;", "SELECT 2;");
+        let changed_migration =
+            Migration::with_checksum(FIRST_MIGRATION, &calculate_checksum("SELECT 2;"));
+
+        assert_eq!(migration, Migration::new(FIRST_MIGRATION));
+        assert_eq!(migration, changed_migration);
+        assert_ne!(migration, Migration::new(SECOND_MIGRATION));
+        assert!(migration.is_drifted_from(&changed_migration));
+        assert!(!migration.is_drifted_from(&Migration::new(FIRST_MIGRATION)));
+    }
+
+    #[test]
+    fn get_migration_sql_content() -> MigraResult<()> {
+        let migration = Migration::with_sql(FIRST_MIGRATION, "SELECT 1;", "SELECT 2;");
+        assert_eq!(migration.upgrade_sql()?, Some("SELECT 1;"));
+        assert_eq!(migration.downgrade_sql()?, Some("SELECT 2;"));
+
+        let migration = Migration::new(FIRST_MIGRATION);
+        assert_eq!(migration.upgrade_sql()?, None);
+        assert_eq!(migration.downgrade_sql()?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn load_migration_from_dir() -> MigraResult<()> {
+        let migration_dir_path = env::temp_dir()
+            .join(format!("migra_{}", std::process::id()))
+            .join("210218232851_create_articles");
+        std::fs::create_dir_all(&migration_dir_path)?;
+        std::fs::write(migration_dir_path.join("up.sql"), "SELECT 1;")?;
+        std::fs::write(migration_dir_path.join("down.sql"), "SELECT 2;")?;
+
+        let migration = Migration::from_dir(&migration_dir_path)?;
+        assert_eq!(migration.name(), "210218232851_create_articles");
+        assert_eq!(migration.source(), Some(migration_dir_path.as_path()));
+        assert_eq!(
+            migration.checksum(),
+            Some(calculate_checksum("SELECT 1;").as_str())
+        );
+
+        std::fs::write(migration_dir_path.join("up.sql"), "SELECT 3;")?;
+        assert_eq!(migration.upgrade_sql()?, Some("SELECT 1;"));
+        assert_eq!(migration.downgrade_sql()?, Some("SELECT 2;"));
+
+        std::fs::remove_dir_all(migration_dir_path.parent().unwrap())?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn return_error_on_migration_dir_without_downgrade() -> MigraResult<()> {
+        let migration_dir_path = env::temp_dir()
+            .join(format!("migra_dir_down_{}", std::process::id()))
+            .join("210218232851_create_articles");
+        std::fs::create_dir_all(&migration_dir_path)?;
+        std::fs::write(migration_dir_path.join("up.sql"), "SELECT 1;")?;
+
+        assert!(matches!(
+            Migration::from_dir(&migration_dir_path),
+            Err(Error::Io(ref err)) if err.kind() == io::ErrorKind::InvalidData
+        ));
+
+        std::fs::write(
+            migration_dir_path.join("up.sql"),
+            "-- migrate:irreversible\nSELECT 1;",
+        )?;
+        assert!(Migration::from_dir(&migration_dir_path)?.is_irreversible());
+
+        std::fs::remove_dir_all(migration_dir_path.parent().unwrap())?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn sort_migrations_by_version() -> MigraResult<()> {
+        let mut list = List::from(vec!["9_create_articles", "10_create_persons", "2_init"]);
+        list.sort_by_version()?;
+
+        assert_eq!(
+            list,
+            List::from(vec!["2_init", "9_create_articles", "10_create_persons"])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn cannot_sort_migrations_without_version() {
+        let mut list = List::from(vec!["9_create_articles", FIRST_MIGRATION]);
+
+        assert!(matches!(
+            list.sort_by_version(),
+            Err(Error::Version(VersionError::Missing(ref name))) if name == FIRST_MIGRATION
+        ));
+    }
+
+    #[test]
+    fn cannot_sort_migrations_with_same_version() {
+        let mut list = List::from(vec!["9_create_articles", "10_init", "9_create_persons"]);
+
+        assert!(matches!(
+            list.sort_by_version(),
+            Err(Error::Version(VersionError::Duplicate { version: 9, ref names }))
+                if names == &["9_create_articles", "9_create_persons"]
+        ));
+    }
+
+    #[test]
+    fn load_migration_from_file() -> MigraResult<()> {
+        let migrations_dir_path =
+            env::temp_dir().join(format!("migra_file_{}", std::process::id()));
+        let migration_file_path = migrations_dir_path.join("210218232851_create_articles.sql");
+        std::fs::create_dir_all(&migrations_dir_path)?;
+        std::fs::write(
+            &migration_file_path,
+            "-- migrate:up\nSELECT 1;\n-- migrate:down\nSELECT 2;\n",
+        )?;
+
+        let migration = Migration::from_file(&migration_file_path)?;
+        assert_eq!(migration.name(), "210218232851_create_articles");
+        assert_eq!(migration.source(), Some(migration_file_path.as_path()));
+        assert_eq!(
+            migration.checksum(),
+            Some(calculate_checksum("SELECT 1;\n").as_str())
+        );
+        assert_eq!(migration.upgrade_sql()?, Some("SELECT 1;\n"));
+        assert_eq!(migration.downgrade_sql()?, Some("SELECT 2;\n"));
+        assert_eq!(migration.upgrade_line_offset(), 1);
+        assert_eq!(migration.downgrade_line_offset(), 3);
+        assert!(!migration.is_irreversible());
+
+        std::fs::write(
+            &migration_file_path,
+            "-- migrate:up\n-- migrate:irreversible\nSELECT 1;\n",
+        )?;
+        let migration = Migration::from_file(&migration_file_path)?;
+        assert!(migration.is_irreversible());
+        assert_eq!(migration.downgrade_sql()?, Some(""));
+
+        std::fs::write(&migration_file_path, "-- migrate:up\nSELECT 1;\n")?;
+        assert!(Migration::from_file(&migration_file_path).is_err());
+
+        std::fs::write(&migration_file_path, "SELECT 1;\n")?;
+        assert!(Migration::from_file(&migration_file_path).is_err());
+
+        std::fs::remove_dir_all(migrations_dir_path)?;
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn return_error_on_invalid_migration_name() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = env::temp_dir().join(OsStr::from_bytes(b"210218232851_\xff.sql"));
+
+        assert!(matches!(
+            Migration::from_dir(&path),
+            Err(Error::Io(ref err)) if err.kind() == io::ErrorKind::InvalidData
+        ));
+        assert!(matches!(
+            Migration::from_file(&path),
+            Err(Error::Io(ref err)) if err.kind() == io::ErrorKind::InvalidData
+        ));
+    }
 }
@@ -0,0 +1,1095 @@
+use crate::clients::Client;
+use crate::errors::{Error, MigraResult};
+use crate::managers::{TransactionStrategy, MIGRATIONS_TABLE_VERSION};
+use crate::migration::{
+    calculate_checksum, has_directive, List, Migration, NO_TRANSACTION_DIRECTIVE,
+};
+use crate::placeholders::Placeholders;
+use std::convert::TryFrom;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// How long [`Migrator`] waits for other migration processes that hold the migrations lock
+/// by default.
+pub const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A source of migrations with their SQL content, such as a migrations directory or
+/// migrations that were embedded into the binary.
+pub trait MigrationSource {
+    /// Returns all migrations with checksums sorted in the order in which they should be
+    /// applied.
+    fn all_migrations(&self) -> MigraResult<List>;
+
+    /// Returns SQL content to upgrade database schema.
+    fn upgrade_sql(&self, migration: &Migration) -> MigraResult<String>;
+
+    /// Returns SQL content to downgrade database schema.
+    fn downgrade_sql(&self, migration: &Migration) -> MigraResult<String>;
+
+    /// Returns the number of lines before the SQL content in the migration source, e.g. before
+    /// the section of the single migration file. It's added to the line of the failed
+    /// statement.
+    ///
+    /// By default returns line offsets of the migration, see
+    /// [`Migration::with_line_offsets`].
+    fn line_offset(&self, migration: &Migration, direction: Direction) -> MigraResult<usize> {
+        Ok(match direction {
+            Direction::Upgrade => migration.upgrade_line_offset(),
+            Direction::Downgrade => migration.downgrade_line_offset(),
+        })
+    }
+}
+
+/// Direction in which the migrations are applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Applies pending migrations.
+    Upgrade,
+
+    /// Rolls back applied migrations.
+    Downgrade,
+}
+
+/// Which migrations should be applied or rolled back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// All pending migrations on upgrade, all applied migrations on downgrade.
+    All,
+
+    /// A number of the first pending migrations on upgrade or the latest applied
+    /// migrations on downgrade.
+    Number(usize),
+
+    /// Only the pending migration with specific name on upgrade. All applied migrations
+    /// down to and including the migration with specific name on downgrade.
+    Name(String),
+}
+
+/// State of migrations in the database compared with the migration source.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Status {
+    applied: List,
+    pending: List,
+    drifted: List,
+}
+
+impl Status {
+    /// Returns applied migrations. The latest applied migration is the first in the list.
+    #[must_use]
+    pub fn applied(&self) -> &List {
+        &self.applied
+    }
+
+    /// Returns migrations from the source that are not applied yet.
+    #[must_use]
+    pub fn pending(&self) -> &List {
+        &self.pending
+    }
+
+    /// Returns applied migrations that were changed in the source after they were applied.
+    ///
+    /// See [`List::drifted`] for more information.
+    #[must_use]
+    pub fn drifted(&self) -> &List {
+        &self.drifted
+    }
+}
+
+/// Migrations that will be applied or rolled back in the specific order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    direction: Direction,
+    migrations: List,
+}
+
+impl Plan {
+    /// Returns direction of the plan.
+    #[must_use]
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Returns migrations in the order in which they will be applied or rolled back.
+    #[must_use]
+    pub fn migrations(&self) -> &List {
+        &self.migrations
+    }
+
+    /// Checks if there is nothing to do.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.migrations.is_empty()
+    }
+}
+
+/// Events of applying or rolling back migrations that are sent to [`ObserveMigrations`].
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum Event<'a> {
+    /// Migrations that will be applied or rolled back in the run were computed.
+    PlanComputed(&'a Plan),
+
+    /// Migration started to apply or roll back.
+    MigrationStarted {
+        /// Direction in which the migration runs.
+        direction: Direction,
+        /// Name of the migration.
+        name: &'a str,
+    },
+
+    /// Migration was applied or rolled back. If the migration runs in a transaction,
+    /// the transaction is not committed yet.
+    MigrationSucceeded {
+        /// Direction in which the migration ran.
+        direction: Direction,
+        /// Name of the migration.
+        name: &'a str,
+        /// How long the migration SQL was executed.
+        duration: Duration,
+    },
+
+    /// Migration failed.
+    MigrationFailed {
+        /// Direction in which the migration ran.
+        direction: Direction,
+        /// Name of the migration.
+        name: &'a str,
+        /// The error of the migration.
+        error: &'a Error,
+    },
+
+    /// The transaction was rolled back after the failure, so changes of its migrations were
+    /// discarded.
+    RolledBack {
+        /// Direction in which the migrations ran.
+        direction: Direction,
+        /// Names of the migrations that ran in the transaction, including the failed one.
+        names: &'a [String],
+    },
+}
+
+/// Used to observe how migrations are applied or rolled back, e.g. to print progress,
+/// write a deploy log or collect metrics.
+pub trait ObserveMigrations {
+    /// Handles the event of the migrations run.
+    fn notify(&mut self, event: &Event<'_>);
+}
+
+/// Applies and rolls back migrations from the migration source using the client.
+///
+/// Migrations are applied while the migrations lock is held. By default all migrations of
+/// one run are applied in a single transaction, see [`TransactionStrategy`] for other
+/// options. Migrations with the [`NO_TRANSACTION_DIRECTIVE`] run outside a transaction, so
+/// the transaction is committed before them and a new one is opened after them. If the run
+/// fails after some migrations were committed, [`Error::PartialRun`] is returned.
+///
+/// The migrations table is created if it doesn't exist.
+///
+/// Progress of the run can be observed with [`ObserveMigrations`], see [`Migrator::observer`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "postgres")]
+/// # fn main() -> migra::Result<()> {
+/// use migra::clients::{OpenDatabaseConnection, PostgresClient};
+/// use migra::migrator::{Direction, Migrator, Target};
+/// use std::path::PathBuf;
+///
+/// let mut client = PostgresClient::new("postgres://postgres@localhost/app")?;
+/// let migrations_dir = PathBuf::from("database/migrations");
+///
+/// let mut migrator = Migrator::new(&mut client, &migrations_dir);
+///
+/// let plan = migrator.plan(Direction::Upgrade, &Target::All)?;
+/// for migration in plan.migrations().iter() {
+///     println!("upgrade {}...", migration.name());
+/// }
+///
+/// migrator.upgrade(&Target::All)?;
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "postgres"))]
+/// # fn main() {}
+/// ```
+pub struct Migrator<'a> {
+    client: &'a mut dyn Client,
+    source: &'a dyn MigrationSource,
+    lock_timeout: Duration,
+    transaction_strategy: TransactionStrategy,
+    observer: Option<&'a mut dyn ObserveMigrations>,
+    placeholders: Option<&'a Placeholders>,
+    allow_drift: bool,
+}
+
+impl fmt::Debug for Migrator<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Migrator")
+            .field("lock_timeout", &self.lock_timeout)
+            .field("transaction_strategy", &self.transaction_strategy)
+            .field("placeholders", &self.placeholders)
+            .field("allow_drift", &self.allow_drift)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> Migrator<'a> {
+    /// Creates new migrator.
+    #[must_use]
+    pub fn new(client: &'a mut dyn Client, source: &'a dyn MigrationSource) -> Self {
+        Migrator {
+            client,
+            source,
+            lock_timeout: DEFAULT_LOCK_TIMEOUT,
+            transaction_strategy: TransactionStrategy::default(),
+            observer: None,
+            placeholders: None,
+            allow_drift: false,
+        }
+    }
+
+    /// Sets how long to wait for other migration processes that hold the migrations lock.
+    ///
+    /// See [`DEFAULT_LOCK_TIMEOUT`] for the default value.
+    #[must_use]
+    pub fn lock_timeout(mut self, lock_timeout: Duration) -> Self {
+        self.lock_timeout = lock_timeout;
+        self
+    }
+
+    /// Sets how migrations of one run are wrapped in transactions.
+    ///
+    /// See [`TransactionStrategy`] for the default value.
+    #[must_use]
+    pub fn transaction_strategy(mut self, transaction_strategy: TransactionStrategy) -> Self {
+        self.transaction_strategy = transaction_strategy;
+        self
+    }
+
+    /// Sets the observer that is notified about events of applying or rolling back
+    /// migrations. See [`Event`] for more information.
+    #[must_use]
+    pub fn observer(mut self, observer: &'a mut dyn ObserveMigrations) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Sets values of placeholders that are substituted in SQL of migrations before it is
+    /// executed. Checksums of applied migrations are calculated from SQL before substitution.
+    ///
+    /// SQL of all migrations of the run is checked before any migration is applied, so
+    /// nothing is applied if any placeholder has no value.
+    #[must_use]
+    pub fn placeholders(mut self, placeholders: &'a Placeholders) -> Self {
+        self.placeholders = Some(placeholders);
+        self
+    }
+
+    /// Sets whether pending migrations are applied even if applied migrations were changed
+    /// in the source after they were applied. Drifted migrations are logged as warnings then.
+    ///
+    /// By default [`Migrator::upgrade`] returns [`Error::Drifted`]. See [`Status::drifted`]
+    /// for more information.
+    #[must_use]
+    pub fn allow_drift(mut self, allow_drift: bool) -> Self {
+        self.allow_drift = allow_drift;
+        self
+    }
+
+    /// Returns applied, pending and drifted migrations.
+    ///
+    /// Applied migrations are marked as irreversible if they are irreversible in the source.
+    ///
+    /// Doesn't create the migrations table if it doesn't exist, but upgrades the existing
+    /// one if it was created by an older version of the crate.
+    pub fn status(&mut self) -> MigraResult<Status> {
+        let applied = match self.client.get_migrations_table_version()? {
+            0 => List::new(),
+            MIGRATIONS_TABLE_VERSION => self.client.get_applied_migrations()?,
+            _ => {
+                self.with_lock(|migrator| migrator.client.create_migrations_table())?;
+                self.client.get_applied_migrations()?
+            }
+        };
+        let all_migrations = self.source.all_migrations()?;
+        let applied = applied
+            .iter()
+            .map(|migration| {
+                let is_irreversible = all_migrations.iter().any(|source_migration| {
+                    source_migration.name() == migration.name()
+                        && source_migration.is_irreversible()
+                });
+                if is_irreversible {
+                    migration.clone().irreversible()
+                } else {
+                    migration.clone()
+                }
+            })
+            .collect::<List>();
+
+        Ok(Status {
+            pending: all_migrations.exclude(&applied),
+            drifted: all_migrations.drifted(&applied),
+            applied,
+        })
+    }
+
+    /// Returns migrations that will be applied or rolled back for the specific target.
+    ///
+    /// Returns [`Error::Irreversible`] if any migration that should be rolled back is
+    /// irreversible.
+    pub fn plan(&mut self, direction: Direction, target: &Target) -> MigraResult<Plan> {
+        let status = self.status()?;
+        plan_from_status(status, direction, target)
+    }
+
+    /// Applies pending migrations for the specific target.
+    ///
+    /// Returns the list of applied migrations. Nothing is applied if applied migrations
+    /// were changed in the source, unless drift is allowed. See [`Migrator::allow_drift`]
+    /// for more information.
+    pub fn upgrade(&mut self, target: &Target) -> MigraResult<List> {
+        self.run(Direction::Upgrade, target)
+    }
+
+    /// Rolls back applied migrations for the specific target.
+    ///
+    /// Returns the list of rolled back migrations. Nothing is rolled back if any migration
+    /// of the target is irreversible. See [`Migrator::plan`] for more information.
+    pub fn downgrade(&mut self, target: &Target) -> MigraResult<List> {
+        self.run(Direction::Downgrade, target)
+    }
+
+    fn run(&mut self, direction: Direction, target: &Target) -> MigraResult<List> {
+        let _span = tracing::info_span!("migrate", ?direction).entered();
+        self.with_lock(|migrator| {
+            migrator.client.create_migrations_table()?;
+            let status = migrator.status()?;
+            if direction == Direction::Upgrade {
+                migrator.check_drift(&status)?;
+            }
+            let plan = plan_from_status(status, direction, target)?;
+            tracing::info!(migrations = plan.migrations.len(), "Computed plan");
+            notify(
+                migrator.observer.as_deref_mut(),
+                &Event::PlanComputed(&plan),
+            );
+            migrator.apply(&plan)?;
+            Ok(plan.migrations)
+        })
+    }
+
+    fn check_drift(&self, status: &Status) -> MigraResult<()> {
+        if status.drifted.is_empty() {
+            return Ok(());
+        }
+
+        if self.allow_drift {
+            for migration in status.drifted.iter() {
+                tracing::warn!("Applied migration {} was changed", migration.name());
+            }
+            Ok(())
+        } else {
+            Err(Error::Drifted(
+                status
+                    .drifted
+                    .iter()
+                    .map(|migration| migration.name().clone())
+                    .collect(),
+            ))
+        }
+    }
+
+    fn with_lock<T, LockFnOnce>(&mut self, lock_fn: LockFnOnce) -> MigraResult<T>
+    where
+        LockFnOnce: FnOnce(&mut Self) -> MigraResult<T>,
+    {
+        tracing::debug!(
+            timeout_ms = u64::try_from(self.lock_timeout.as_millis()).unwrap_or(u64::MAX),
+            "Acquiring migrations lock"
+        );
+        self.client.acquire_lock(self.lock_timeout)?;
+        tracing::debug!("Acquired migrations lock");
+
+        let res = lock_fn(self);
+        let release_res = self.client.release_lock();
+        tracing::debug!("Released migrations lock");
+
+        let value = res?;
+        release_res?;
+        Ok(value)
+    }
+
+    /// Reads SQL content of the planned migrations and substitutes placeholders in it.
+    ///
+    /// Returns names of migrations with their original content and SQL to execute.
+    fn read_migrations<'p>(
+        &self,
+        plan: &'p Plan,
+    ) -> MigraResult<Vec<(&'p String, String, String)>> {
+        plan.migrations
+            .iter()
+            .map(|migration| {
+                match plan.direction {
+                    Direction::Upgrade => self.source.upgrade_sql(migration),
+                    Direction::Downgrade => self.source.downgrade_sql(migration),
+                }
+                .and_then(|content| {
+                    let sql = match self.placeholders {
+                        Some(placeholders) => {
+                            placeholders.expand_migration(migration.name(), &content)?
+                        }
+                        None => content.clone(),
+                    };
+                    Ok((migration.name(), content, sql))
+                })
+            })
+            .collect()
+    }
+
+    fn apply(&mut self, plan: &Plan) -> MigraResult<()> {
+        if plan.is_empty() {
+            return Ok(());
+        }
+
+        let migrations_with_content = self.read_migrations(plan)?;
+
+        let client = &mut *self.client;
+        let mut observer = self.observer.as_deref_mut();
+        let mut committed = Vec::new();
+
+        let strategy = self.transaction_strategy;
+        let in_transaction = |content: &str| {
+            strategy != TransactionStrategy::None
+                && !has_directive(content, NO_TRANSACTION_DIRECTIVE)
+        };
+
+        let batches = split_into_batches(
+            &migrations_with_content,
+            |(_, content, _), (_, next_content, _)| {
+                strategy == TransactionStrategy::Single
+                    && in_transaction(content)
+                    && in_transaction(next_content)
+            },
+        );
+        for batch in batches {
+            let in_transaction = in_transaction(&batch[0].1);
+            let mut started = 0;
+
+            let res = if in_transaction {
+                client
+                    .begin_transaction()
+                    .and_then(|()| {
+                        run_batch(
+                            client,
+                            observer.as_deref_mut(),
+                            plan.direction,
+                            batch,
+                            &mut started,
+                        )
+                    })
+                    .and_then(|()| client.commit_transaction())
+            } else {
+                run_batch(
+                    client,
+                    observer.as_deref_mut(),
+                    plan.direction,
+                    batch,
+                    &mut started,
+                )
+            };
+
+            let Err(err) = res else {
+                committed.extend(batch.iter().map(|(name, ..)| (*name).clone()));
+                continue;
+            };
+
+            let started = started.max(1);
+            let failed = batch[started - 1].0;
+            let err = with_source_line_offset(self.source, plan, failed, err);
+            tracing::info!(migration = failed.as_str(), error = %err, "Migration failed");
+            notify(
+                observer.as_deref_mut(),
+                &Event::MigrationFailed {
+                    direction: plan.direction,
+                    name: failed,
+                    error: &err,
+                },
+            );
+
+            let err = if in_transaction {
+                match client.rollback_transaction() {
+                    Ok(()) => {
+                        let names = batch[..started]
+                            .iter()
+                            .map(|(name, ..)| (*name).clone())
+                            .collect::<Vec<_>>();
+                        tracing::info!(
+                            migrations = %names.join(", "),
+                            "Rolled back the transaction"
+                        );
+                        notify(
+                            observer.as_deref_mut(),
+                            &Event::RolledBack {
+                                direction: plan.direction,
+                                names: &names,
+                            },
+                        );
+                        err
+                    }
+                    Err(rollback_err) => rollback_err,
+                }
+            } else {
+                err
+            };
+
+            return Err(if in_transaction && committed.is_empty() {
+                err
+            } else {
+                Error::partial_run(failed, committed, in_transaction, err)
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Computes migrations that will be applied or rolled back for the specific target.
+fn plan_from_status(status: Status, direction: Direction, target: &Target) -> MigraResult<Plan> {
+    let migrations = match direction {
+        Direction::Upgrade => match target {
+            Target::All => status.pending,
+            Target::Number(number) => status.pending.iter().take(*number).collect(),
+            Target::Name(name) => status
+                .pending
+                .iter()
+                .filter(|migration| migration.name() == name)
+                .collect(),
+        },
+        Direction::Downgrade => match target {
+            Target::All => status.applied,
+            Target::Number(number) => status.applied.iter().take(*number).collect(),
+            Target::Name(name) => match status
+                .applied
+                .iter()
+                .position(|migration| migration.name() == name)
+            {
+                Some(position) => status.applied.iter().take(position + 1).collect(),
+                None => List::new(),
+            },
+        },
+    };
+
+    if direction == Direction::Downgrade {
+        if let Some(migration) = migrations.iter().find(|m| m.is_irreversible()) {
+            return Err(Error::Irreversible(migration.name().clone()));
+        }
+    }
+
+    Ok(Plan {
+        direction,
+        migrations,
+    })
+}
+
+/// Splits items into batches of consecutive items, where each pair of neighbours
+/// is joined by the predicate.
+fn split_into_batches<T>(items: &[T], mut is_joined: impl FnMut(&T, &T) -> bool) -> Vec<&[T]> {
+    let mut batches = Vec::new();
+    let mut rest = items;
+
+    while !rest.is_empty() {
+        let len = 1 + rest
+            .windows(2)
+            .take_while(|pair| is_joined(&pair[0], &pair[1]))
+            .count();
+        let (batch, next_rest) = rest.split_at(len);
+        batches.push(batch);
+        rest = next_rest;
+    }
+
+    batches
+}
+
+/// Runs migrations of one batch and counts started migrations to find the failed one.
+fn run_batch(
+    client: &mut dyn Client,
+    mut observer: Option<&mut (dyn ObserveMigrations + '_)>,
+    direction: Direction,
+    batch: &[(&String, String, String)],
+    started: &mut usize,
+) -> MigraResult<()> {
+    for (name, content, sql) in batch {
+        *started += 1;
+        let _span = tracing::info_span!("migration", migration = name.as_str()).entered();
+        notify(
+            observer.as_deref_mut(),
+            &Event::MigrationStarted { direction, name },
+        );
+        // SQL with substituted placeholders may contain secrets, so only the original
+        // content is logged.
+        tracing::trace!(sql = content.as_str(), "Migration SQL");
+
+        let started_at = Instant::now();
+        match direction {
+            Direction::Upgrade => {
+                client.run_upgrade_migration_with_checksum(name, sql, &calculate_checksum(content))
+            }
+            Direction::Downgrade => client.run_downgrade_migration(name, sql),
+        }?;
+        let duration = started_at.elapsed();
+        tracing::info!(
+            duration_ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
+            "Migration succeeded"
+        );
+        notify(
+            observer.as_deref_mut(),
+            &Event::MigrationSucceeded {
+                direction,
+                name,
+                duration,
+            },
+        );
+    }
+
+    Ok(())
+}
+
+/// Moves the line of the failed statement to the line in the migration source, e.g. in
+/// the single migration file.
+fn with_source_line_offset(
+    source: &dyn MigrationSource,
+    plan: &Plan,
+    name: &str,
+    err: Error,
+) -> Error {
+    let line_offset = plan
+        .migrations
+        .iter()
+        .find(|migration| migration.name() == name)
+        .and_then(|migration| source.line_offset(migration, plan.direction).ok());
+
+    match line_offset {
+        Some(line_offset) => err.with_line_offset(line_offset),
+        None => err,
+    }
+}
+
+fn notify(observer: Option<&mut (dyn ObserveMigrations + '_)>, event: &Event<'_>) {
+    if let Some(observer) = observer {
+        observer.notify(event);
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+    use crate::clients::{OpenDatabaseConnection, SqliteClient};
+    use crate::embedded::{EmbeddedMigration, EmbeddedMigrations};
+    use crate::managers::ManageMigrations;
+
+    const FIRST_MIGRATION: &str = "210218232851_create_articles";
+    const SECOND_MIGRATION: &str = "210218233414_create_persons";
+    const THIRD_MIGRATION: &str = "210218233512_create_comments";
+
+    const MIGRATIONS: EmbeddedMigrations = EmbeddedMigrations::new(&[
+        EmbeddedMigration::new(
+            FIRST_MIGRATION,
+            "CREATE TABLE articles (title text NOT NULL);",
+            "DROP TABLE articles;",
+        ),
+        EmbeddedMigration::new(
+            SECOND_MIGRATION,
+            "CREATE TABLE persons (name text NOT NULL);",
+            "DROP TABLE persons;",
+        ),
+        EmbeddedMigration::new(
+            THIRD_MIGRATION,
+            "CREATE TABLE comments (content text NOT NULL);",
+            "DROP TABLE comments;",
+        ),
+    ]);
+
+    fn migration_names(list: &List) -> Vec<&str> {
+        list.iter()
+            .map(|migration| migration.name().as_str())
+            .collect()
+    }
+
+    #[test]
+    fn get_status_without_migrations_table() -> MigraResult<()> {
+        let mut client = SqliteClient::new(":memory:")?;
+        let status = Migrator::new(&mut client, &MIGRATIONS).status()?;
+
+        assert_eq!(status.applied(), &List::new());
+        assert_eq!(status.pending(), &MIGRATIONS.list());
+        assert_eq!(status.drifted(), &List::new());
+        assert_eq!(client.get_migrations_table_version()?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn plan_upgrade_migrations() -> MigraResult<()> {
+        let mut client = SqliteClient::new(":memory:")?;
+        let mut migrator = Migrator::new(&mut client, &MIGRATIONS);
+
+        let plan = migrator.plan(Direction::Upgrade, &Target::Number(2))?;
+        assert_eq!(
+            migration_names(plan.migrations()),
+            vec![FIRST_MIGRATION, SECOND_MIGRATION]
+        );
+
+        let plan = migrator.plan(Direction::Upgrade, &Target::Name(SECOND_MIGRATION.into()))?;
+        assert_eq!(migration_names(plan.migrations()), vec![SECOND_MIGRATION]);
+
+        let plan = migrator.plan(Direction::Upgrade, &Target::Name(String::from("unknown")))?;
+        assert!(plan.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn upgrade_and_downgrade_migrations() -> MigraResult<()> {
+        let mut client = SqliteClient::new(":memory:")?;
+        let mut migrator = Migrator::new(&mut client, &MIGRATIONS);
+
+        let applied = migrator.upgrade(&Target::Number(1))?;
+        assert_eq!(migration_names(&applied), vec![FIRST_MIGRATION]);
+
+        let applied = migrator.upgrade(&Target::All)?;
+        assert_eq!(
+            migration_names(&applied),
+            vec![SECOND_MIGRATION, THIRD_MIGRATION]
+        );
+        assert!(migrator.status()?.pending().is_empty());
+
+        let rolled_back = migrator.downgrade(&Target::Name(SECOND_MIGRATION.into()))?;
+        assert_eq!(
+            migration_names(&rolled_back),
+            vec![THIRD_MIGRATION, SECOND_MIGRATION]
+        );
+
+        let rolled_back = migrator.downgrade(&Target::All)?;
+        assert_eq!(migration_names(&rolled_back), vec![FIRST_MIGRATION]);
+        assert_eq!(migrator.status()?.pending(), &MIGRATIONS.list());
+
+        Ok(())
+    }
+
+    #[test]
+    fn rollback_failed_upgrade() -> MigraResult<()> {
+        const INVALID_MIGRATIONS: EmbeddedMigrations = EmbeddedMigrations::new(&[
+            EmbeddedMigration::new(
+                FIRST_MIGRATION,
+                "CREATE TABLE articles (title text NOT NULL);",
+                "DROP TABLE articles;",
+            ),
+            EmbeddedMigration::new(SECOND_MIGRATION, "CREATE TABLE", "SELECT 1;"),
+        ]);
+
+        let mut client = SqliteClient::new(":memory:")?;
+        let mut migrator = Migrator::new(&mut client, &INVALID_MIGRATIONS);
+
+        assert!(migrator.upgrade(&Target::All).is_err());
+        assert_eq!(migrator.status()?.pending(), &INVALID_MIGRATIONS.list());
+
+        Ok(())
+    }
+
+    #[test]
+    fn reject_upgrade_with_drifted_migrations() -> MigraResult<()> {
+        let mut client = SqliteClient::new(":memory:")?;
+        Migrator::new(&mut client, &MIGRATIONS).upgrade(&Target::Number(1))?;
+        client.apply_sql("UPDATE migrations SET checksum = 'changed'")?;
+
+        let mut migrator = Migrator::new(&mut client, &MIGRATIONS);
+        assert!(matches!(
+            migrator.upgrade(&Target::All),
+            Err(Error::Drifted(ref names)) if names == &[FIRST_MIGRATION]
+        ));
+        assert!(migrator.downgrade(&Target::All).is_ok());
+
+        let mut migrator = Migrator::new(&mut client, &MIGRATIONS);
+        migrator.upgrade(&Target::Number(1))?;
+        client.apply_sql("UPDATE migrations SET checksum = 'changed'")?;
+
+        let applied = Migrator::new(&mut client, &MIGRATIONS)
+            .allow_drift(true)
+            .upgrade(&Target::All)?;
+        assert_eq!(
+            migration_names(&applied),
+            vec![SECOND_MIGRATION, THIRD_MIGRATION]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn substitute_placeholders_in_migrations() -> MigraResult<()> {
+        const MIGRATIONS: EmbeddedMigrations = EmbeddedMigrations::new(&[
+            EmbeddedMigration::new(
+                FIRST_MIGRATION,
+                "CREATE TABLE ${table} (title text NOT NULL);",
+                "DROP TABLE ${table};",
+            ),
+            EmbeddedMigration::new(
+                SECOND_MIGRATION,
+                "CREATE TABLE persons (name text NOT NULL DEFAULT '${author}');",
+                "DROP TABLE persons;",
+            ),
+        ]);
+
+        let mut client = SqliteClient::new(":memory:")?;
+        let placeholders = Placeholders::new().with("table", "posts");
+
+        let res = Migrator::new(&mut client, &MIGRATIONS)
+            .placeholders(&placeholders)
+            .upgrade(&Target::All);
+        assert!(matches!(
+            res,
+            Err(Error::UnresolvedPlaceholder { ref name, migration: Some(ref migration) })
+                if name == "author" && migration == SECOND_MIGRATION
+        ));
+        assert!(client.get_applied_migrations()?.is_empty());
+
+        let placeholders = placeholders.with("author", "anonymous");
+        let mut migrator = Migrator::new(&mut client, &MIGRATIONS).placeholders(&placeholders);
+        migrator.upgrade(&Target::All)?;
+        assert!(migrator.status()?.drifted().is_empty());
+
+        client.apply_sql("INSERT INTO posts (title) VALUES ('Hello')")?;
+        assert_eq!(
+            client
+                .get_applied_migrations()?
+                .iter()
+                .rev()
+                .collect::<List>(),
+            MIGRATIONS.list()
+        );
+
+        Migrator::new(&mut client, &MIGRATIONS)
+            .placeholders(&placeholders)
+            .downgrade(&Target::All)?;
+        assert!(client.apply_sql("SELECT * FROM posts").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn notify_observer_about_events() -> MigraResult<()> {
+        const INVALID_MIGRATIONS: EmbeddedMigrations = EmbeddedMigrations::new(&[
+            EmbeddedMigration::new(
+                FIRST_MIGRATION,
+                "CREATE TABLE articles (title text NOT NULL);",
+                "DROP TABLE articles;",
+            ),
+            EmbeddedMigration::new(SECOND_MIGRATION, "CREATE TABLE", "SELECT 1;"),
+        ]);
+
+        #[derive(Default)]
+        struct EventLog(Vec<String>);
+
+        impl ObserveMigrations for EventLog {
+            fn notify(&mut self, event: &Event<'_>) {
+                self.0.push(match *event {
+                    Event::PlanComputed(plan) => format!("plan {}", plan.migrations().len()),
+                    Event::MigrationStarted { name, .. } => format!("start {name}"),
+                    Event::MigrationSucceeded { name, .. } => format!("success {name}"),
+                    Event::MigrationFailed { name, .. } => format!("fail {name}"),
+                    Event::RolledBack { names, .. } => format!("rollback {}", names.join(", ")),
+                });
+            }
+        }
+
+        let mut client = SqliteClient::new(":memory:")?;
+        let mut event_log = EventLog::default();
+
+        let res = Migrator::new(&mut client, &INVALID_MIGRATIONS)
+            .observer(&mut event_log)
+            .upgrade(&Target::All);
+        assert!(res.is_err());
+
+        assert_eq!(
+            event_log.0,
+            vec![
+                String::from("plan 2"),
+                format!("start {FIRST_MIGRATION}"),
+                format!("success {FIRST_MIGRATION}"),
+                format!("start {SECOND_MIGRATION}"),
+                format!("fail {SECOND_MIGRATION}"),
+                format!("rollback {FIRST_MIGRATION}, {SECOND_MIGRATION}"),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn stop_downgrade_at_irreversible_migration() -> MigraResult<()> {
+        const IRREVERSIBLE_MIGRATIONS: EmbeddedMigrations = EmbeddedMigrations::new(&[
+            EmbeddedMigration::new(
+                FIRST_MIGRATION,
+                "CREATE TABLE articles (title text NOT NULL);",
+                "DROP TABLE articles;",
+            ),
+            EmbeddedMigration::new(
+                SECOND_MIGRATION,
+                "-- migrate:irreversible\nDROP TABLE articles;",
+                "",
+            ),
+            EmbeddedMigration::new(
+                THIRD_MIGRATION,
+                "CREATE TABLE comments (content text NOT NULL);",
+                "DROP TABLE comments;",
+            ),
+        ]);
+
+        let mut client = SqliteClient::new(":memory:")?;
+        let mut migrator = Migrator::new(&mut client, &IRREVERSIBLE_MIGRATIONS);
+
+        migrator.upgrade(&Target::All)?;
+        let status = migrator.status()?;
+        assert_eq!(
+            status
+                .applied()
+                .iter()
+                .map(Migration::is_irreversible)
+                .collect::<Vec<_>>(),
+            vec![false, true, false]
+        );
+
+        let rolled_back = migrator.downgrade(&Target::Number(1))?;
+        assert_eq!(migration_names(&rolled_back), vec![THIRD_MIGRATION]);
+
+        assert!(matches!(
+            migrator.downgrade(&Target::All),
+            Err(Error::Irreversible(ref name)) if name == SECOND_MIGRATION
+        ));
+        assert_eq!(migrator.status()?.applied().len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_migration_outside_transaction() -> MigraResult<()> {
+        const VACUUM_MIGRATIONS: EmbeddedMigrations = EmbeddedMigrations::new(&[
+            EmbeddedMigration::new(
+                FIRST_MIGRATION,
+                "CREATE TABLE articles (title text NOT NULL);",
+                "DROP TABLE articles;",
+            ),
+            EmbeddedMigration::new(
+                SECOND_MIGRATION,
+                "-- migrate:no-transaction\nVACUUM;",
+                "-- migrate:no-transaction\nVACUUM;",
+            ),
+            EmbeddedMigration::new(
+                THIRD_MIGRATION,
+                "CREATE TABLE comments (content text NOT NULL);",
+                "DROP TABLE comments;",
+            ),
+        ]);
+
+        let mut client = SqliteClient::new(":memory:")?;
+        let mut migrator = Migrator::new(&mut client, &VACUUM_MIGRATIONS);
+
+        let applied = migrator.upgrade(&Target::All)?;
+        assert_eq!(applied.len(), 3);
+
+        let rolled_back = migrator.downgrade(&Target::All)?;
+        assert_eq!(rolled_back.len(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn report_partial_run() -> MigraResult<()> {
+        const PARTIAL_MIGRATIONS: EmbeddedMigrations = EmbeddedMigrations::new(&[
+            EmbeddedMigration::new(
+                FIRST_MIGRATION,
+                "CREATE TABLE articles (title text NOT NULL);",
+                "DROP TABLE articles;",
+            ),
+            EmbeddedMigration::new(
+                SECOND_MIGRATION,
+                "-- migrate:no-transaction\nCREATE TABLE persons (name text NOT NULL);\nCREATE",
+                "DROP TABLE persons;",
+            ),
+        ]);
+
+        let mut client = SqliteClient::new(":memory:")?;
+        let mut migrator = Migrator::new(&mut client, &PARTIAL_MIGRATIONS);
+
+        match migrator.upgrade(&Target::All) {
+            Err(Error::PartialRun(ref error)) => {
+                assert_eq!(error.failed(), SECOND_MIGRATION);
+                assert_eq!(error.committed(), &[String::from(FIRST_MIGRATION)]);
+                assert!(!error.in_transaction());
+            }
+            res => panic!("Unexpected result: {:?}", res),
+        }
+        assert_eq!(
+            migration_names(migrator.status()?.pending()),
+            vec![SECOND_MIGRATION]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn report_migration_of_failed_sql() -> MigraResult<()> {
+        const INVALID_MIGRATIONS: EmbeddedMigrations =
+            EmbeddedMigrations::new(&[EmbeddedMigration::new(
+                FIRST_MIGRATION,
+                "SELECT unknown;",
+                "SELECT 1;",
+            )]);
+
+        let mut client = SqliteClient::new(":memory:")?;
+        let mut migrator = Migrator::new(&mut client, &INVALID_MIGRATIONS);
+
+        match migrator.upgrade(&Target::All) {
+            Err(Error::Db(ref error)) => {
+                assert_eq!(error.migration(), Some(FIRST_MIGRATION));
+                assert!(error.to_string().starts_with(&format!(
+                    r#"Failed to apply sql in "{FIRST_MIGRATION}" migration - "#
+                )));
+            }
+            res => panic!("Unexpected result: {:?}", res),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn keep_committed_migrations_with_per_migration_transactions() -> MigraResult<()> {
+        const INVALID_MIGRATIONS: EmbeddedMigrations = EmbeddedMigrations::new(&[
+            EmbeddedMigration::new(
+                FIRST_MIGRATION,
+                "CREATE TABLE articles (title text NOT NULL);",
+                "DROP TABLE articles;",
+            ),
+            EmbeddedMigration::new(SECOND_MIGRATION, "CREATE TABLE", "SELECT 1;"),
+        ]);
+
+        let mut client = SqliteClient::new(":memory:")?;
+        let mut migrator = Migrator::new(&mut client, &INVALID_MIGRATIONS)
+            .transaction_strategy(TransactionStrategy::PerMigration);
+
+        match migrator.upgrade(&Target::All) {
+            Err(Error::PartialRun(ref error)) => {
+                assert_eq!(error.failed(), SECOND_MIGRATION);
+                assert_eq!(error.committed(), &[String::from(FIRST_MIGRATION)]);
+                assert!(error.in_transaction());
+            }
+            res => panic!("Unexpected result: {:?}", res),
+        }
+        assert_eq!(
+            migration_names(migrator.status()?.applied()),
+            vec![FIRST_MIGRATION]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,219 @@
+use crate::errors::{Error, MigraResult};
+use std::collections::BTreeMap;
+use std::iter::FromIterator;
+
+/// Values of placeholders that are substituted in migration SQL before it is executed.
+///
+/// Placeholders look like `${name}`, where the name contains only letters, digits and `_`.
+/// Use `$${name}` to keep `${name}` in SQL as is. Other uses of `$`, such as dollar-quoted
+/// strings, are not changed.
+///
+/// Placeholders are substituted everywhere in SQL, including string literals and comments,
+/// because SQL is not parsed.
+///
+/// **Note:** Checksums of migrations are always calculated from SQL before placeholders are
+/// substituted, so the same migration has the same checksum in all environments.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> migra::Result<()> {
+/// use migra::placeholders::Placeholders;
+///
+/// let placeholders = Placeholders::new()
+///     .with("schema", "app")
+///     .with("app_role", "app_user");
+///
+/// assert_eq!(
+///     placeholders.expand("GRANT USAGE ON SCHEMA ${schema} TO ${app_role};")?,
+///     "GRANT USAGE ON SCHEMA app TO app_user;"
+/// );
+/// assert!(placeholders.expand("SET search_path TO ${tenant};").is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Placeholders {
+    values: BTreeMap<String, String>,
+}
+
+impl Placeholders {
+    /// Creates placeholders without values.
+    #[must_use]
+    pub fn new() -> Self {
+        Placeholders::default()
+    }
+
+    /// Sets the value of the placeholder with specific name.
+    #[must_use]
+    pub fn with(mut self, name: &str, value: &str) -> Self {
+        self.insert(name, value);
+        self
+    }
+
+    /// Sets the value of the placeholder with specific name. The previous value is replaced.
+    pub fn insert(&mut self, name: &str, value: &str) {
+        self.values.insert(name.to_owned(), value.to_owned());
+    }
+
+    /// Returns the value of the placeholder with specific name.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    /// Checks if there are no values.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Substitutes placeholders in SQL with their values.
+    ///
+    /// Returns [`Error::UnresolvedPlaceholder`] if any placeholder has no value.
+    pub fn expand(&self, sql: &str) -> MigraResult<String> {
+        let mut expanded = String::with_capacity(sql.len());
+        let mut rest = sql;
+
+        while let Some(start) = rest.find("${") {
+            let (before, placeholder) = rest.split_at(start);
+            let Some(name) = parse_name(&placeholder[2..]) else {
+                expanded.push_str(before);
+                expanded.push_str("${");
+                rest = &placeholder[2..];
+                continue;
+            };
+            let (placeholder, after) = placeholder.split_at(name.len() + 3);
+
+            if let Some(before) = before.strip_suffix('$') {
+                expanded.push_str(before);
+                expanded.push_str(placeholder);
+            } else {
+                let value = self
+                    .get(name)
+                    .ok_or_else(|| Error::unresolved_placeholder(name))?;
+                expanded.push_str(before);
+                expanded.push_str(value);
+            }
+            rest = after;
+        }
+
+        expanded.push_str(rest);
+        Ok(expanded)
+    }
+
+    /// Substitutes placeholders in SQL of the migration with specific name.
+    ///
+    /// Same as [`Placeholders::expand`], but the returned [`Error::UnresolvedPlaceholder`]
+    /// contains the name of the migration.
+    pub fn expand_migration(&self, name: &str, sql: &str) -> MigraResult<String> {
+        self.expand(sql).map_err(|err| err.with_migration(name))
+    }
+}
+
+impl<N: AsRef<str>, V: AsRef<str>> FromIterator<(N, V)> for Placeholders {
+    fn from_iter<I: IntoIterator<Item = (N, V)>>(iter: I) -> Self {
+        let mut placeholders = Placeholders::new();
+        placeholders.extend(iter);
+        placeholders
+    }
+}
+
+impl<N: AsRef<str>, V: AsRef<str>> Extend<(N, V)> for Placeholders {
+    fn extend<I: IntoIterator<Item = (N, V)>>(&mut self, iter: I) {
+        for (name, value) in iter {
+            self.insert(name.as_ref(), value.as_ref());
+        }
+    }
+}
+
+/// Returns the name of the placeholder if the text starts with a valid name followed by `}`.
+fn parse_name(text: &str) -> Option<&str> {
+    let len = text
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(text.len());
+    if len > 0 && text[len..].starts_with('}') {
+        Some(&text[..len])
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_placeholders() -> MigraResult<()> {
+        let placeholders = Placeholders::new()
+            .with("schema", "app")
+            .with("tablespace", "fast_ssd");
+
+        assert_eq!(
+            placeholders.expand(
+                "CREATE TABLE ${schema}.articles (id int) TABLESPACE ${tablespace};\n\
+                 CREATE TABLE ${schema}.persons (id int);"
+            )?,
+            "CREATE TABLE app.articles (id int) TABLESPACE fast_ssd;\n\
+             CREATE TABLE app.persons (id int);"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn keep_escaped_placeholders_and_dollar_quotes() -> MigraResult<()> {
+        let placeholders = Placeholders::new().with("schema", "app");
+
+        assert_eq!(
+            placeholders.expand("SELECT '$${schema}', '${}', '${ schema }', '$${'")?,
+            "SELECT '${schema}', '${}', '${ schema }', '$${'"
+        );
+        assert_eq!(
+            placeholders.expand("DO $$ BEGIN PERFORM 1; END $$; DO $body$ SELECT $1 $body$")?,
+            "DO $$ BEGIN PERFORM 1; END $$; DO $body$ SELECT $1 $body$"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn expand_placeholders_in_literals_and_comments() -> MigraResult<()> {
+        let placeholders = Placeholders::new().with("password", "secret");
+
+        assert_eq!(
+            placeholders.expand(
+                "-- ${password} is set here\n\
+                 CREATE ROLE app LOGIN PASSWORD '${password}'; /* $${password} */"
+            )?,
+            "-- secret is set here\n\
+             CREATE ROLE app LOGIN PASSWORD 'secret'; /* ${password} */"
+        );
+        assert!(placeholders
+            .expand("-- GRANT USAGE ON SCHEMA ${old_schema} TO app;")
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn return_error_on_unresolved_placeholder() {
+        let placeholders = Placeholders::new().with("schema", "app");
+
+        assert!(matches!(
+            placeholders.expand("GRANT USAGE ON SCHEMA ${schema} TO ${app_role}"),
+            Err(Error::UnresolvedPlaceholder { ref name, migration: None }) if name == "app_role"
+        ));
+    }
+
+    #[test]
+    fn return_error_with_migration_name_on_unresolved_placeholder() {
+        let placeholders = Placeholders::new();
+
+        assert!(matches!(
+            placeholders.expand_migration("010_grant_usage", "GRANT USAGE TO ${app_role}"),
+            Err(Error::UnresolvedPlaceholder { ref name, migration: Some(ref migration) })
+                if name == "app_role" && migration == "010_grant_usage"
+        ));
+    }
+}
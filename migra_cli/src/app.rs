@@ -24,7 +24,7 @@ impl App {
         Config::read(self.config_path())
     }
 
-    pub fn run_command(&self) -> migra::StdResult<()> {
+    pub fn run_command(&self) -> MigraResult<()> {
         match self.app_opt.command.clone() {
             Command::Init => {
                 commands::initialize_migra_manifest(self)?;
@@ -44,6 +44,12 @@ impl App {
             Command::Downgrade(ref cmd_opts) => {
                 commands::rollback_applied_migrations(self, cmd_opts)?;
             }
+            Command::Seed(ref cmd_opts) => {
+                commands::run_seeds(self, cmd_opts)?;
+            }
+            Command::History => {
+                commands::print_migrations_history(self)?;
+            }
             Command::Completions(cmd_opts) => {
                 AppOpt::clap().gen_completions_to(
                     env!("CARGO_BIN_NAME"),
@@ -1,9 +1,11 @@
 use crate::app::App;
 use crate::database;
+use crate::error::MigraResult;
 use crate::opts::ApplyCommandOpt;
 
-pub(crate) fn apply_sql(app: &App, cmd_opts: &ApplyCommandOpt) -> migra::StdResult<()> {
+pub(crate) fn apply_sql(app: &App, cmd_opts: &ApplyCommandOpt) -> MigraResult<()> {
     let config = app.config()?;
+    let placeholders = config.migrations.placeholders()?;
     let mut client = database::create_client_from_config(&config)?;
 
     let file_contents = cmd_opts
@@ -17,14 +19,16 @@ pub(crate) fn apply_sql(app: &App, cmd_opts: &ApplyCommandOpt) -> migra::StdResu
             }
             file_path
         })
-        .map(std::fs::read_to_string)
-        .collect::<Result<Vec<_>, _>>()?;
+        .map(|file_path| {
+            let content = std::fs::read_to_string(file_path)?;
+            Ok(placeholders.expand(&content)?)
+        })
+        .collect::<MigraResult<Vec<_>>>()?;
 
     database::run_in_transaction(&mut client, |client| {
         file_contents
             .iter()
             .try_for_each(|content| client.apply_sql(content))
-            .map_err(From::from)
     })?;
 
     Ok(())
@@ -1,50 +1,46 @@
 use crate::app::App;
-use crate::database;
+use crate::error::MigraResult;
 use crate::opts::DowngradeCommandOpt;
-use std::cmp;
+use crate::progress::PrintProgress;
+use crate::Config;
+use migra::clients::Client;
+use migra::migrator::Target;
+use migra::Migrator;
 
 pub(crate) fn rollback_applied_migrations(
     app: &App,
     opts: &DowngradeCommandOpt,
-) -> migra::StdResult<()> {
-    let config = app.config()?;
-    let mut client = database::create_client_from_config(&config)?;
-
-    client.create_migrations_table()?;
+) -> MigraResult<()> {
+    super::run_migrations(app, opts.dry_run, |client, config| {
+        rollback(client, config, opts)
+    })
+}
 
+fn rollback(
+    client: &mut dyn Client,
+    config: &Config,
+    opts: &DowngradeCommandOpt,
+) -> MigraResult<()> {
     let migrations_dir_path = config.migration_dir_path();
-    let applied_migrations = client.get_applied_migrations()?;
-    let all_migrations = migra::fs::get_all_migrations(&migrations_dir_path)?;
+    let lock_timeout = super::lock_timeout(config, opts.lock_timeout);
+    let transaction_strategy =
+        super::transaction_strategy(config, opts.single_transaction, opts.transaction_strategy);
 
-    let rollback_migrations_number = if opts.all_migrations {
-        applied_migrations.len()
+    let mut progress = PrintProgress::new(opts.dry_run);
+    let placeholders = config.migrations.placeholders()?;
+    let mut migrator = Migrator::new(client, &migrations_dir_path)
+        .lock_timeout(lock_timeout)
+        .transaction_strategy(transaction_strategy.into())
+        .placeholders(&placeholders)
+        .observer(&mut progress);
+
+    let target = if opts.all_migrations {
+        Target::All
     } else {
-        cmp::min(opts.migrations_number, applied_migrations.len())
+        Target::Number(opts.migrations_number)
     };
 
-    let migrations = applied_migrations[..rollback_migrations_number].to_vec();
-    let migrations_with_content = migrations
-        .iter()
-        .map(|migration| {
-            let migration_name = migration.name();
-            let migration_file_path = migrations_dir_path.join(migration_name).join("down.sql");
-            std::fs::read_to_string(migration_file_path).map(|content| (migration_name, content))
-        })
-        .collect::<Result<Vec<_>, _>>()?;
-
-    database::run_in_transaction(&mut client, |client| {
-        migrations_with_content
-            .iter()
-            .try_for_each(|(migration_name, content)| {
-                if all_migrations.contains_name(migration_name) {
-                    println!("downgrade {}...", migration_name);
-                    client.run_downgrade_migration(migration_name, content)
-                } else {
-                    Ok(())
-                }
-            })
-            .map_err(From::from)
-    })?;
+    migrator.downgrade(&target)?;
 
     Ok(())
 }
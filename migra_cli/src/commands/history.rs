@@ -0,0 +1,74 @@
+use crate::app::App;
+use crate::database;
+use crate::error::MigraResult;
+use chrono::{DateTime, Local};
+use migra::migration;
+
+const EM_DASH: char = '—';
+
+pub(crate) fn print_migrations_history(app: &App) -> MigraResult<()> {
+    let config = app.config()?;
+    let mut client = database::create_client_from_config(&config)?;
+
+    // The history is read without the migrations lock, so the migrations table is neither
+    // created nor upgraded. Legacy tables have no checksums and metadata.
+    let history = client.get_migrations_history()?;
+
+    println!("Migrations history:");
+    if history.is_empty() {
+        println!("{EM_DASH}");
+        return Ok(());
+    }
+
+    let rows = history
+        .iter()
+        .rev()
+        .map(|applied_migration| {
+            [
+                applied_migration.migration().name().clone(),
+                format_applied_at(applied_migration),
+                format_duration(applied_migration),
+                applied_migration
+                    .applied_by()
+                    .map_or_else(|| EM_DASH.to_string(), ToOwned::to_owned),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let name_width = column_width(&rows, 0);
+    let applied_at_width = column_width(&rows, 1);
+    let duration_width = column_width(&rows, 2);
+
+    for [name, applied_at, duration, applied_by] in rows {
+        println!(
+            "{name:name_width$}  {applied_at:applied_at_width$}  {duration:>duration_width$}  {applied_by}"
+        );
+    }
+
+    Ok(())
+}
+
+fn format_applied_at(applied_migration: &migration::AppliedMigration) -> String {
+    applied_migration.applied_at().map_or_else(
+        || EM_DASH.to_string(),
+        |applied_at| {
+            DateTime::<Local>::from(applied_at)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        },
+    )
+}
+
+fn format_duration(applied_migration: &migration::AppliedMigration) -> String {
+    applied_migration.duration().map_or_else(
+        || EM_DASH.to_string(),
+        |duration| format!("{}ms", duration.as_millis()),
+    )
+}
+
+fn column_width(rows: &[[String; 4]], column: usize) -> usize {
+    rows.iter()
+        .map(|row| row[column].chars().count())
+        .max()
+        .unwrap_or_default()
+}
@@ -1,8 +1,10 @@
 use crate::app::App;
 use crate::config::{Config, MIGRA_TOML_FILENAME};
+use crate::error::MigraResult;
+use std::io;
 use std::path::PathBuf;
 
-pub(crate) fn initialize_migra_manifest(app: &App) -> migra::StdResult<()> {
+pub(crate) fn initialize_migra_manifest(app: &App) -> MigraResult<()> {
     let config_path = app.config_path().cloned().map_or_else(
         || PathBuf::from(MIGRA_TOML_FILENAME),
         |mut config_path| {
@@ -16,7 +18,7 @@ pub(crate) fn initialize_migra_manifest(app: &App) -> migra::StdResult<()> {
     );
 
     if config_path.exists() {
-        println!("{} already exists", config_path.to_str().unwrap());
+        println!("{} already exists", config_path.display());
         return Ok(());
     }
 
@@ -25,10 +27,11 @@ pub(crate) fn initialize_migra_manifest(app: &App) -> migra::StdResult<()> {
     }
 
     let config = Config::default();
-    let content = toml::to_string(&config)?;
+    let content =
+        toml::to_string(&config).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
     std::fs::write(&config_path, content)?;
 
-    println!("Created {}", config_path.to_str().unwrap());
+    println!("Created {}", config_path.display());
 
     Ok(())
 }
@@ -1,66 +1,86 @@
 use crate::app::App;
 use crate::database;
-use crate::error::Error;
+use crate::error::{Error, MigraResult};
 use migra::migration;
+use migra::Migrator;
 
 const EM_DASH: char = '—';
 
-pub(crate) fn print_migration_lists(app: &App) -> migra::StdResult<()> {
+pub(crate) fn print_migration_lists(app: &App) -> MigraResult<()> {
     let config = app.config()?;
-    let applied_migrations = match config.database.connection_string() {
+    let migrations_dir_path = config.migration_dir_path();
+    let status = match config.database.connection_string() {
         Ok(ref database_connection_string) => {
-            let mut client = database::create_client(
-                &config.database.client(),
-                database_connection_string,
-                &config.migrations.table_name(),
-            )?;
-            let applied_migrations = client.get_applied_migrations().unwrap_or_else(|err| {
-                dbg!(err);
-                migration::List::new()
-            });
+            let mut client = database::create_client(&config, database_connection_string)?;
+            let status = Migrator::new(&mut *client, &migrations_dir_path).status()?;
 
-            show_applied_migrations(&applied_migrations);
+            show_applied_migrations(status.applied());
 
-            applied_migrations
+            Some(status)
         }
         Err(e) if e == Error::MissedEnvVar(String::new()) => {
-            eprintln!("WARNING: {}", e);
-            eprintln!("WARNING: No connection to database");
+            tracing::warn!("{e}");
+            tracing::warn!("No connection to database");
 
-            migration::List::new()
+            None
         }
-        Err(e) => panic!("{}", e),
+        Err(e) => return Err(e),
     };
 
     println!();
 
-    let all_migrations = migra::fs::get_all_migrations(&config.migration_dir_path())?;
-    let pending_migrations = all_migrations.exclude(&applied_migrations);
+    let (pending_migrations, drifted_migrations) = match status {
+        Some(status) => (status.pending().clone(), status.drifted().clone()),
+        None => (
+            migra::fs::get_all_migrations(&migrations_dir_path)?,
+            migration::List::new(),
+        ),
+    };
 
     show_pending_migrations(&pending_migrations);
 
+    if !drifted_migrations.is_empty() {
+        println!();
+        show_drifted_migrations(&drifted_migrations);
+    }
+
     Ok(())
 }
 
 fn show_applied_migrations(applied_migrations: &migration::List) {
     println!("Applied migrations:");
     if applied_migrations.is_empty() {
-        println!("{}", EM_DASH);
+        println!("{EM_DASH}");
     } else {
         applied_migrations
             .iter()
             .rev()
-            .for_each(|migration| println!("{}", migration.name()));
+            .for_each(|migration| println!("{}", format_migration(migration)));
     }
 }
 
 fn show_pending_migrations(pending_migrations: &migration::List) {
     println!("Pending migrations:");
     if pending_migrations.is_empty() {
-        println!("{}", EM_DASH);
+        println!("{EM_DASH}");
     } else {
         pending_migrations.iter().for_each(|migration| {
-            println!("{}", migration.name());
+            println!("{}", format_migration(migration));
         });
     }
 }
+
+fn show_drifted_migrations(drifted_migrations: &migration::List) {
+    println!("Drifted migrations:");
+    drifted_migrations.iter().for_each(|migration| {
+        println!("{}", migration.name());
+    });
+}
+
+fn format_migration(migration: &migration::Migration) -> String {
+    if migration.is_irreversible() {
+        format!("{} (irreversible)", migration.name())
+    } else {
+        migration.name().clone()
+    }
+}
@@ -1,12 +1,19 @@
 use crate::app::App;
+use crate::error::{Error, MigraResult};
 use crate::opts::MakeCommandOpt;
 use chrono::Local;
+use migra::migration::{DOWN_SECTION_MARKER, UP_SECTION_MARKER};
 use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
 
-pub(crate) fn make_migration(app: &App, opts: &MakeCommandOpt) -> migra::StdResult<()> {
+pub(crate) fn make_migration(app: &App, opts: &MakeCommandOpt) -> MigraResult<()> {
     let config = app.config()?;
     let date_format = config.migrations.date_format();
-    let formatted_current_timestamp = Local::now().format(&date_format);
+    let formatted_current_timestamp = Local::now().format(&date_format).to_string();
+    if migra::migration::parse_version(&formatted_current_timestamp).is_none() {
+        return Err(Error::InvalidDateFormat(date_format));
+    }
 
     let migration_name: String = opts
         .migration_name
@@ -18,10 +25,23 @@ pub(crate) fn make_migration(app: &App, opts: &MakeCommandOpt) -> migra::StdResu
         })
         .collect();
 
-    let migration_dir_path = config.migration_dir_path().join(format!(
-        "{}_{}",
-        formatted_current_timestamp, migration_name
-    ));
+    let migration_full_name = format!("{formatted_current_timestamp}_{migration_name}");
+    let migration_path = if opts.single_file {
+        make_migration_file(&config.migration_dir_path(), &migration_full_name)?
+    } else {
+        make_migration_dir(&config.migration_dir_path(), &migration_full_name)?
+    };
+
+    println!(
+        "Structure for migration has been created in the {}",
+        migration_path.display()
+    );
+
+    Ok(())
+}
+
+fn make_migration_dir(migrations_dir_path: &Path, migration_name: &str) -> io::Result<PathBuf> {
+    let migration_dir_path = migrations_dir_path.join(migration_name);
     if !migration_dir_path.exists() {
         fs::create_dir_all(&migration_dir_path)?;
     }
@@ -39,10 +59,23 @@ pub(crate) fn make_migration(app: &App, opts: &MakeCommandOpt) -> migra::StdResu
         )?;
     }
 
-    println!(
-        "Structure for migration has been created in the {}",
-        migration_dir_path.to_str().unwrap()
-    );
+    Ok(migration_dir_path)
+}
 
-    Ok(())
+fn make_migration_file(migrations_dir_path: &Path, migration_name: &str) -> io::Result<PathBuf> {
+    if !migrations_dir_path.exists() {
+        fs::create_dir_all(migrations_dir_path)?;
+    }
+
+    let migration_file_path = migrations_dir_path.join(format!("{migration_name}.sql"));
+    if !migration_file_path.exists() {
+        fs::write(
+            &migration_file_path,
+            format!(
+                "{UP_SECTION_MARKER}\n-- Your SQL goes here\n\n{DOWN_SECTION_MARKER}\n-- This section should undo anything in the upgrade section\n\n"
+            ),
+        )?;
+    }
+
+    Ok(migration_file_path)
 }
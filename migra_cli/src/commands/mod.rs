@@ -1,13 +1,65 @@
 mod apply;
 mod downgrade;
+mod history;
 mod init;
 mod list;
 mod make;
+mod seed;
 mod upgrade;
 
 pub(crate) use apply::*;
 pub(crate) use downgrade::*;
+pub(crate) use history::*;
 pub(crate) use init::*;
 pub(crate) use list::*;
 pub(crate) use make::*;
+pub(crate) use seed::*;
 pub(crate) use upgrade::*;
+
+use crate::app::App;
+use crate::config::TransactionStrategy;
+use crate::database;
+use crate::error::MigraResult;
+use crate::Config;
+use migra::clients::{Client, DryRunClient};
+use std::time::Duration;
+
+/// Runs migrations with the client from the config. On dry run the SQL is printed instead
+/// of being executed.
+fn run_migrations<RunFn>(app: &App, dry_run: bool, run_fn: RunFn) -> MigraResult<()>
+where
+    RunFn: FnOnce(&mut dyn Client, &Config) -> MigraResult<()>,
+{
+    let config = app.config()?;
+    let mut client = database::create_client_from_config(&config)?;
+
+    if dry_run {
+        let mut dry_run_client = DryRunClient::new(&mut *client);
+        run_fn(&mut dry_run_client, &config)?;
+        if !dry_run_client.statements().is_empty() {
+            println!("{}", dry_run_client.script());
+        }
+        Ok(())
+    } else {
+        run_fn(&mut *client, &config)
+    }
+}
+
+/// Lock timeout from the `--lock-timeout` option or from the config.
+fn lock_timeout(config: &Config, lock_timeout: Option<u64>) -> Duration {
+    lock_timeout.map_or_else(|| config.migrations.lock_timeout(), Duration::from_secs)
+}
+
+/// Transaction strategy from the `--single-transaction` and `--transaction-strategy` options
+/// or from the config.
+fn transaction_strategy(
+    config: &Config,
+    single_transaction: bool,
+    transaction_strategy: Option<TransactionStrategy>,
+) -> TransactionStrategy {
+    if single_transaction {
+        TransactionStrategy::Single
+    } else {
+        transaction_strategy.unwrap_or_else(|| config.migrations.transaction_strategy())
+    }
+}
@@ -0,0 +1,174 @@
+use crate::app::App;
+use crate::database;
+use crate::error::{Error, MigraResult};
+use crate::opts::SeedCommandOpt;
+use migra::clients::AnyClient;
+use migra::migration;
+use migra::placeholders::Placeholders;
+use std::path::{Component, Path};
+use std::{fs, io};
+
+/// Seed file with reference or development data.
+#[derive(Debug)]
+struct Seed {
+    /// Name of the seed file without extension.
+    file_name: String,
+    /// Version from the prefix of the file name, by which seeds are sorted.
+    version: Option<u64>,
+    /// Name that is stored in the seeds table. Seeds of an environment are prefixed with
+    /// the environment name, e.g. `dev/010_users`.
+    name: String,
+    content: String,
+}
+
+pub(crate) fn run_seeds(app: &App, opts: &SeedCommandOpt) -> MigraResult<()> {
+    let config = app.config()?;
+    let seeds = read_seeds(&config.seed_dir_path(), opts.environment.as_deref())?;
+    let placeholders = config.migrations.placeholders()?;
+    let lock_timeout = super::lock_timeout(&config, opts.lock_timeout);
+
+    let mut client = database::create_seeds_client_from_config(&config)?;
+
+    client.acquire_lock(lock_timeout)?;
+    let res = run_pending_seeds(&mut client, &seeds, &placeholders, opts.force);
+    let release_res = client.release_lock();
+
+    res?;
+    release_res?;
+    Ok(())
+}
+
+fn run_pending_seeds(
+    client: &mut AnyClient,
+    seeds: &[Seed],
+    placeholders: &Placeholders,
+    force: bool,
+) -> MigraResult<()> {
+    client.create_migrations_table()?;
+    let applied_seeds = client.get_applied_migrations()?;
+
+    let mut pending_seeds = Vec::new();
+    for seed in seeds {
+        let checksum = migration::calculate_checksum(&seed.content);
+        let applied_seed = applied_seeds
+            .iter()
+            .find(|applied_seed| applied_seed.name() == &seed.name);
+
+        match applied_seed {
+            Some(_) if force => {}
+            Some(applied_seed) => {
+                if applied_seed
+                    .checksum()
+                    .is_some_and(|applied| applied != checksum)
+                {
+                    tracing::warn!(
+                        "Seed {} was changed after it ran. Use --force to rerun it",
+                        seed.name
+                    );
+                }
+                continue;
+            }
+            None => {}
+        }
+
+        // All placeholders are checked before any seed runs.
+        let sql = placeholders.expand_migration(&seed.name, &seed.content)?;
+        pending_seeds.push((seed, sql, checksum, applied_seed.is_some()));
+    }
+
+    if pending_seeds.is_empty() {
+        println!("Seeds are up to date");
+        return Ok(());
+    }
+
+    database::run_in_transaction(client, |client| {
+        pending_seeds
+            .iter()
+            .try_for_each(|(seed, sql, checksum, is_applied)| {
+                println!("seed {}...", seed.name);
+                if *is_applied {
+                    client.delete_migration(&seed.name)?;
+                }
+                client.run_upgrade_migration_with_checksum(&seed.name, sql, checksum)
+            })
+    })?;
+
+    Ok(())
+}
+
+/// Reads common seeds and seeds of the environment sorted by version like migrations.
+/// Seeds without version run after versioned seeds, and common seeds run before seeds of
+/// the environment with the same file name.
+fn read_seeds(dir_path: &Path, environment: Option<&str>) -> MigraResult<Vec<Seed>> {
+    let mut seeds = read_seed_files(dir_path, None)?;
+
+    if let Some(environment) = environment {
+        if !is_environment_name(environment) {
+            return Err(Error::InvalidEnvironment(environment.to_owned()));
+        }
+
+        let environment_dir_path = dir_path.join(environment);
+        if !environment_dir_path.is_dir() {
+            tracing::warn!(
+                "Cannot find {} directory with seeds of {} environment",
+                environment_dir_path.display(),
+                environment
+            );
+        }
+        seeds.extend(read_seed_files(&environment_dir_path, Some(environment))?);
+    }
+
+    // The sort is stable, so common seeds, which are read first, stay before seeds of the
+    // environment.
+    seeds.sort_by(|seed, other| {
+        (seed.version.is_none(), seed.version, &seed.file_name).cmp(&(
+            other.version.is_none(),
+            other.version,
+            &other.file_name,
+        ))
+    });
+    Ok(seeds)
+}
+
+/// Checks that the environment is a plain directory name, so seeds are never read outside
+/// of the seeds directory.
+fn is_environment_name(environment: &str) -> bool {
+    let mut components = Path::new(environment).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    ) && !environment.contains(&['/', '\\'][..])
+}
+
+fn read_seed_files(dir_path: &Path, environment: Option<&str>) -> MigraResult<Vec<Seed>> {
+    let entries = match dir_path.read_dir() {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        entries => entries?,
+    };
+
+    let mut seeds = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if !path.is_file() || path.extension().map_or(true, |ext| ext != "sql") {
+            continue;
+        }
+
+        let file_name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let name = match environment {
+            Some(environment) => format!("{environment}/{file_name}"),
+            None => file_name.clone(),
+        };
+
+        seeds.push(Seed {
+            content: fs::read_to_string(&path)?,
+            version: migration::parse_version(&file_name),
+            file_name,
+            name,
+        });
+    }
+
+    Ok(seeds)
+}
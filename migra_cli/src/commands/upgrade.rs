@@ -1,66 +1,51 @@
 use crate::app::App;
-use crate::database;
+use crate::error::{Error, MigraResult};
 use crate::opts::UpgradeCommandOpt;
-use migra::migration;
-
-pub(crate) fn upgrade_pending_migrations(
-    app: &App,
-    opts: &UpgradeCommandOpt,
-) -> migra::StdResult<()> {
-    let config = app.config()?;
-    let mut client = database::create_client_from_config(&config)?;
-
-    client.create_migrations_table()?;
+use crate::progress::PrintProgress;
+use crate::Config;
+use migra::clients::Client;
+use migra::migrator::Target;
+use migra::Migrator;
+
+pub(crate) fn upgrade_pending_migrations(app: &App, opts: &UpgradeCommandOpt) -> MigraResult<()> {
+    super::run_migrations(app, opts.dry_run, |client, config| {
+        upgrade(client, config, opts)
+    })
+}
 
+fn upgrade(client: &mut dyn Client, config: &Config, opts: &UpgradeCommandOpt) -> MigraResult<()> {
+    let mut progress = PrintProgress::new(opts.dry_run);
     let migrations_dir_path = config.migration_dir_path();
-    let applied_migration_names = client.get_applied_migrations()?;
-    let all_migrations = migra::fs::get_all_migrations(&migrations_dir_path)?;
-
-    let pending_migrations = all_migrations.exclude(&applied_migration_names);
-    if pending_migrations.is_empty() {
-        println!("Up to date");
-        return Ok(());
-    }
+    let lock_timeout = super::lock_timeout(config, opts.lock_timeout);
+    let transaction_strategy =
+        super::transaction_strategy(config, opts.single_transaction, opts.transaction_strategy);
+
+    let placeholders = config.migrations.placeholders()?;
+    let target = match (&opts.migration_name, opts.migrations_number) {
+        (Some(migration_name), _) => Target::Name(migration_name.clone()),
+        (None, Some(migrations_number)) => Target::Number(migrations_number),
+        (None, None) => Target::All,
+    };
 
-    let migrations: migration::List = if let Some(migration_name) = opts.migration_name.clone() {
-        let target_migration = (*pending_migrations)
-            .clone()
-            .into_iter()
-            .find(|m| m.name() == &migration_name);
-        if let Some(migration) = target_migration {
-            vec![migration].into()
+    let applied = Migrator::new(client, &migrations_dir_path)
+        .lock_timeout(lock_timeout)
+        .transaction_strategy(transaction_strategy.into())
+        .placeholders(&placeholders)
+        .allow_drift(opts.allow_drift)
+        .observer(&mut progress)
+        .upgrade(&target)
+        .map_err(|err| match err {
+            migra::Error::Drifted(names) => Error::DriftedMigrations(names),
+            err => Error::Migra(err),
+        })?;
+
+    if applied.is_empty() {
+        if let Target::Name(migration_name) = target {
+            tracing::warn!(r#"Cannot find pending migration with "{migration_name}" name"#);
         } else {
-            eprintln!(r#"Cannot find migration with "{}" name"#, migration_name);
-            return Ok(());
+            progress.print("Up to date");
         }
-    } else {
-        let upgrade_migrations_number = opts
-            .migrations_number
-            .unwrap_or_else(|| pending_migrations.len());
-
-        pending_migrations[..upgrade_migrations_number]
-            .to_vec()
-            .into()
-    };
-
-    let migrations_with_content = migrations
-        .iter()
-        .map(|migration| {
-            let migration_name = migration.name();
-            let migration_file_path = migrations_dir_path.join(migration_name).join("up.sql");
-            std::fs::read_to_string(migration_file_path).map(|content| (migration_name, content))
-        })
-        .collect::<Result<Vec<_>, _>>()?;
-
-    database::run_in_transaction(&mut client, |client| {
-        migrations_with_content
-            .iter()
-            .try_for_each(|(migration_name, content)| {
-                println!("upgrade {}...", migration_name);
-                client.run_upgrade_migration(migration_name, content)
-            })
-            .map_err(From::from)
-    })?;
+    }
 
     Ok(())
 }
@@ -1,6 +1,13 @@
 use crate::error::{Error, MigraResult};
+#[cfg(feature = "postgres")]
+use migra::clients::PostgresTlsConfig;
+use migra::migrator::DEFAULT_LOCK_TIMEOUT;
+use migra::placeholders::Placeholders;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
 use std::{env, fs};
 
 //===========================================================================//
@@ -18,29 +25,32 @@ fn search_for_directory_containing_file(path: &Path, file_name: &str) -> MigraRe
     }
 }
 
+/// Reads the option value from the environment variable if the value starts with `$`.
+/// Falls back to the default value with a warning if the variable is not set.
+fn resolve_env_value(value: &str, option_name: &str, default_value: fn() -> String) -> String {
+    value.strip_prefix('$').map_or_else(
+        || value.to_owned(),
+        |value_env| {
+            env::var(value_env).unwrap_or_else(|_| {
+                let default_value = default_value();
+                tracing::warn!(
+                    "Cannot read {} variable and use {} {} by default",
+                    value_env,
+                    default_value,
+                    option_name
+                );
+                default_value
+            })
+        },
+    )
+}
+
 fn recursive_find_project_root() -> MigraResult<PathBuf> {
     let current_dir = std::env::current_dir()?;
 
     search_for_directory_containing_file(&current_dir, MIGRA_TOML_FILENAME)
 }
 
-#[cfg(any(
-    not(feature = "postgres"),
-    not(feature = "mysql"),
-    not(feature = "sqlite")
-))]
-macro_rules! please_install_with {
-    (feature $database_name:expr) => {
-        panic!(
-            r#"You cannot use migra for "{database_name}".
-You need to reinstall crate with "{database_name}" feature.
-
-cargo install migra-cli --features ${database_name}"#,
-            database_name = $database_name
-        );
-    };
-}
-
 //===========================================================================//
 // Database config                                                           //
 //===========================================================================//
@@ -49,25 +59,24 @@ fn is_sqlite_database_file(filename: &str) -> bool {
     filename
         .rsplit('.')
         .next()
-        .map(|ext| ext.eq_ignore_ascii_case("db"))
-        .unwrap_or_default()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("db"))
 }
 
 fn default_database_connection_env() -> String {
     String::from("$DATABASE_URL")
 }
 
+/// Database clients that can be configured. Clients that are not enabled in features
+/// are rejected when the connection is opened.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum SupportedDatabaseClient {
-    #[cfg(feature = "postgres")]
     Postgres,
-    #[cfg(feature = "mysql")]
     Mysql,
-    #[cfg(feature = "sqlite")]
     Sqlite,
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for SupportedDatabaseClient {
     fn default() -> Self {
         cfg_if! {
@@ -88,6 +97,15 @@ pub(crate) struct DatabaseConfig {
 
     #[serde(default = "default_database_connection_env")]
     pub connection: String,
+
+    /// Root certificate to verify the server certificate of TLS connections.
+    pub ssl_root_cert: Option<PathBuf>,
+
+    /// Client certificate that is sent to the server of TLS connections.
+    pub ssl_cert: Option<PathBuf>,
+
+    /// Private key of the client certificate.
+    pub ssl_key: Option<PathBuf>,
 }
 
 impl Default for DatabaseConfig {
@@ -95,6 +113,9 @@ impl Default for DatabaseConfig {
         DatabaseConfig {
             connection: default_database_connection_env(),
             client: None,
+            ssl_root_cert: None,
+            ssl_cert: None,
+            ssl_key: None,
         }
     }
 }
@@ -106,29 +127,11 @@ impl DatabaseConfig {
                 .ok()
                 .and_then(|connection_string| {
                     if connection_string.starts_with("postgres://") {
-                        cfg_if! {
-                            if #[cfg(feature = "postgres")] {
-                                Some(SupportedDatabaseClient::Postgres)
-                            } else {
-                                please_install_with!(feature "postgres")
-                            }
-                        }
+                        Some(SupportedDatabaseClient::Postgres)
                     } else if connection_string.starts_with("mysql://") {
-                        cfg_if! {
-                            if #[cfg(feature = "mysql")] {
-                                Some(SupportedDatabaseClient::Mysql)
-                            } else {
-                                please_install_with!(feature "mysql")
-                            }
-                        }
+                        Some(SupportedDatabaseClient::Mysql)
                     } else if is_sqlite_database_file(&connection_string) {
-                        cfg_if! {
-                            if #[cfg(feature = "sqlite")] {
-                                Some(SupportedDatabaseClient::Sqlite)
-                            } else {
-                                please_install_with!(feature "sqlite")
-                            }
-                        }
+                        Some(SupportedDatabaseClient::Sqlite)
                     } else {
                         None
                     }
@@ -160,6 +163,40 @@ fn default_migrations_table_name() -> String {
     String::from("migrations")
 }
 
+/// Prefix of environment variables that set values of placeholders.
+const PLACEHOLDER_ENV_PREFIX: &str = "MIGRA_PLACEHOLDER_";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum TransactionStrategy {
+    Single,
+    PerMigration,
+    None,
+}
+
+impl FromStr for TransactionStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "single" => Ok(TransactionStrategy::Single),
+            "per-migration" => Ok(TransactionStrategy::PerMigration),
+            "none" => Ok(TransactionStrategy::None),
+            _ => Err(format!(r#"Unknown transaction strategy "{s}""#)),
+        }
+    }
+}
+
+impl From<TransactionStrategy> for migra::managers::TransactionStrategy {
+    fn from(strategy: TransactionStrategy) -> Self {
+        match strategy {
+            TransactionStrategy::Single => Self::Single,
+            TransactionStrategy::PerMigration => Self::PerMigration,
+            TransactionStrategy::None => Self::None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct MigrationsConfig {
     #[serde(rename = "directory", default = "default_migrations_directory")]
@@ -169,6 +206,13 @@ pub(crate) struct MigrationsConfig {
     table_name: String,
 
     date_format: Option<String>,
+
+    lock_timeout: Option<u64>,
+
+    transaction_strategy: Option<TransactionStrategy>,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    placeholders: BTreeMap<String, String>,
 }
 
 impl Default for MigrationsConfig {
@@ -177,40 +221,23 @@ impl Default for MigrationsConfig {
             directory: default_migrations_directory(),
             table_name: default_migrations_table_name(),
             date_format: None,
+            lock_timeout: None,
+            transaction_strategy: None,
+            placeholders: BTreeMap::new(),
         }
     }
 }
 
 impl MigrationsConfig {
     pub fn directory(&self) -> String {
-        self.directory.strip_prefix('$').map_or_else(
-            || self.directory.clone(),
-            |directory_env| {
-                env::var(directory_env).unwrap_or_else(|_| {
-                    println!(
-                        "WARN: Cannot read {} variable and use {} directory by default",
-                        directory_env,
-                        default_migrations_directory()
-                    );
-                    default_migrations_directory()
-                })
-            },
-        )
+        resolve_env_value(&self.directory, "directory", default_migrations_directory)
     }
 
     pub fn table_name(&self) -> String {
-        self.table_name.strip_prefix('$').map_or_else(
-            || self.table_name.clone(),
-            |table_name_env| {
-                env::var(table_name_env).unwrap_or_else(|_| {
-                    println!(
-                        "WARN: Cannot read {} variable and use {} table_name by default",
-                        table_name_env,
-                        default_migrations_table_name()
-                    );
-                    default_migrations_table_name()
-                })
-            },
+        resolve_env_value(
+            &self.table_name,
+            "table_name",
+            default_migrations_table_name,
         )
     }
 
@@ -219,6 +246,87 @@ impl MigrationsConfig {
             .clone()
             .unwrap_or_else(|| String::from("%y%m%d%H%M%S"))
     }
+
+    /// How long to wait for other migration processes that hold the migrations lock.
+    pub fn lock_timeout(&self) -> Duration {
+        self.lock_timeout
+            .map_or(DEFAULT_LOCK_TIMEOUT, Duration::from_secs)
+    }
+
+    /// How migrations of one run are wrapped in transactions.
+    pub fn transaction_strategy(&self) -> TransactionStrategy {
+        self.transaction_strategy
+            .unwrap_or(TransactionStrategy::Single)
+    }
+
+    /// Values of placeholders that are substituted in migration SQL.
+    ///
+    /// Values from the `[migrations.placeholders]` table, that start with `$`, are read from
+    /// the environment variable with that name. Environment variables with
+    /// the `MIGRA_PLACEHOLDER_` prefix override the table, e.g. `MIGRA_PLACEHOLDER_APP_ROLE`
+    /// sets the `app_role` placeholder.
+    pub fn placeholders(&self) -> MigraResult<Placeholders> {
+        let mut placeholders = self
+            .placeholders
+            .iter()
+            .map(|(name, value)| {
+                let value = match value.strip_prefix('$') {
+                    Some(value_env) => env::var(value_env)
+                        .map_err(|_| Error::MissedEnvVar(value_env.to_owned()))?,
+                    None => value.clone(),
+                };
+                Ok((name, value))
+            })
+            .collect::<MigraResult<Placeholders>>()?;
+
+        placeholders.extend(env::vars().filter_map(|(key, value)| {
+            key.strip_prefix(PLACEHOLDER_ENV_PREFIX)
+                .map(|name| (name.to_lowercase(), value))
+        }));
+
+        Ok(placeholders)
+    }
+}
+
+//===========================================================================//
+// Seeds config                                                              //
+//===========================================================================//
+
+fn default_seeds_directory() -> String {
+    String::from("seeds")
+}
+
+fn default_seeds_table_name() -> String {
+    String::from("migra_seeds")
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct SeedsConfig {
+    #[serde(default = "default_seeds_directory")]
+    directory: String,
+
+    #[serde(default = "default_seeds_table_name")]
+    table_name: String,
+}
+
+impl Default for SeedsConfig {
+    fn default() -> Self {
+        SeedsConfig {
+            directory: default_seeds_directory(),
+            table_name: default_seeds_table_name(),
+        }
+    }
+}
+
+impl SeedsConfig {
+    pub fn directory(&self) -> String {
+        resolve_env_value(&self.directory, "directory", default_seeds_directory)
+    }
+
+    /// Name of the table that tracks seeds that have run.
+    pub fn table_name(&self) -> String {
+        resolve_env_value(&self.table_name, "table_name", default_seeds_table_name)
+    }
 }
 
 //===========================================================================//
@@ -239,6 +347,9 @@ pub struct Config {
 
     #[serde(default)]
     pub(crate) migrations: MigrationsConfig,
+
+    #[serde(default)]
+    pub(crate) seeds: SeedsConfig,
 }
 
 impl Default for Config {
@@ -248,6 +359,7 @@ impl Default for Config {
             root: PathBuf::from("database"),
             database: DatabaseConfig::default(),
             migrations: MigrationsConfig::default(),
+            seeds: SeedsConfig::default(),
         }
     }
 }
@@ -267,9 +379,11 @@ impl Config {
         match config_path {
             None => Ok(Config::default()),
             Some(config_path) => {
-                let content = fs::read_to_string(&config_path)?;
+                let content = fs::read_to_string(&config_path)
+                    .map_err(|err| Error::ReadConfig(config_path.clone(), err))?;
 
-                let mut config: Config = toml::from_str(&content).expect("Cannot parse Migra.toml");
+                let mut config: Config = toml::from_str(&content)
+                    .map_err(|err| Error::InvalidConfig(config_path.clone(), err))?;
                 config.manifest_root = config_path
                     .parent()
                     .unwrap_or_else(|| Path::new(""))
@@ -287,4 +401,30 @@ impl Config {
     pub fn migration_dir_path(&self) -> PathBuf {
         self.directory_path().join(self.migrations.directory())
     }
+
+    pub fn seed_dir_path(&self) -> PathBuf {
+        self.directory_path().join(self.seeds.directory())
+    }
+
+    /// TLS settings of the `Postgres` connection. Certificate paths are relative to the
+    /// directory of `Migra.toml`.
+    #[cfg(feature = "postgres")]
+    pub fn postgres_tls_config(&self) -> MigraResult<PostgresTlsConfig> {
+        let mut tls_config = PostgresTlsConfig::new();
+
+        if let Some(ref root_cert) = self.database.ssl_root_cert {
+            tls_config = tls_config.root_cert(self.manifest_root.join(root_cert));
+        }
+
+        match (&self.database.ssl_cert, &self.database.ssl_key) {
+            (Some(cert), Some(key)) => {
+                tls_config = tls_config
+                    .client_cert(self.manifest_root.join(cert), self.manifest_root.join(key));
+            }
+            (None, None) => {}
+            _ => return Err(Error::IncompleteClientCert),
+        }
+
+        Ok(tls_config)
+    }
 }
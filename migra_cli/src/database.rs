@@ -1,23 +1,33 @@
 use crate::config::SupportedDatabaseClient;
+use crate::error::MigraResult;
 use crate::Config;
+#[cfg(not(all(feature = "postgres", feature = "mysql", feature = "sqlite")))]
+use crate::Error;
+use migra::clients::AnyClient;
 #[cfg(feature = "mysql")]
 use migra::clients::MysqlClient;
+#[cfg(any(feature = "mysql", feature = "sqlite"))]
+use migra::clients::OpenDatabaseConnection;
 #[cfg(feature = "postgres")]
 use migra::clients::PostgresClient;
 #[cfg(feature = "sqlite")]
 use migra::clients::SqliteClient;
-use migra::clients::{AnyClient, OpenDatabaseConnection};
 
-pub fn create_client(
-    client_kind: &SupportedDatabaseClient,
+pub fn create_client(config: &Config, connection_string: &str) -> MigraResult<AnyClient> {
+    create_client_with_table_name(config, connection_string, &config.migrations.table_name())
+}
+
+fn create_client_with_table_name(
+    config: &Config,
     connection_string: &str,
     migrations_table_name: &str,
-) -> migra::Result<AnyClient> {
-    let client: AnyClient = match client_kind {
+) -> MigraResult<AnyClient> {
+    let client: AnyClient = match config.database.client() {
         #[cfg(feature = "postgres")]
-        SupportedDatabaseClient::Postgres => Box::new(PostgresClient::manual(
+        SupportedDatabaseClient::Postgres => Box::new(PostgresClient::with_tls(
             connection_string,
             migrations_table_name,
+            &config.postgres_tls_config()?,
         )?),
         #[cfg(feature = "mysql")]
         SupportedDatabaseClient::Mysql => Box::new(MysqlClient::manual(
@@ -29,18 +39,28 @@ pub fn create_client(
             connection_string,
             migrations_table_name,
         )?),
+        #[cfg(not(feature = "postgres"))]
+        SupportedDatabaseClient::Postgres => return Err(Error::MissedFeature("postgres")),
+        #[cfg(not(feature = "mysql"))]
+        SupportedDatabaseClient::Mysql => return Err(Error::MissedFeature("mysql")),
+        #[cfg(not(feature = "sqlite"))]
+        SupportedDatabaseClient::Sqlite => return Err(Error::MissedFeature("sqlite")),
     };
 
     Ok(client)
 }
 
-pub fn create_client_from_config(config: &Config) -> migra::StdResult<AnyClient> {
-    create_client(
-        &config.database.client(),
+pub fn create_client_from_config(config: &Config) -> MigraResult<AnyClient> {
+    create_client(config, &config.database.connection_string()?)
+}
+
+/// Creates a client that tracks seeds in the seeds table instead of the migrations table.
+pub fn create_seeds_client_from_config(config: &Config) -> MigraResult<AnyClient> {
+    create_client_with_table_name(
+        config,
         &config.database.connection_string()?,
-        &config.migrations.table_name(),
+        &config.seeds.table_name(),
     )
-    .map_err(From::from)
 }
 
 pub fn run_in_transaction<TrxFnMut>(client: &mut AnyClient, trx_fn: TrxFnMut) -> migra::Result<()>
@@ -49,7 +69,7 @@ where
 {
     client
         .begin_transaction()
-        .and_then(|_| trx_fn(client))
+        .and_then(|()| trx_fn(client))
         .and_then(|res| client.commit_transaction().and(Ok(res)))
         .or_else(|err| client.rollback_transaction().and(Err(err)))
 }
@@ -1,16 +1,43 @@
+use migra::errors::DbKind;
 use std::error;
 use std::fmt;
 use std::io;
 use std::mem;
+use std::path::PathBuf;
 use std::result;
 
 pub type MigraResult<T> = result::Result<T, Error>;
 
+/// Exit codes of the command line tool, which scripts can rely on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    /// Unexpected errors, e.g. I/O errors.
+    Failure = 1,
+    /// Invalid command line arguments.
+    Usage = 2,
+    /// Invalid or incomplete configuration.
+    Config = 3,
+    /// Cannot connect to the database.
+    Connection = 4,
+    /// Migrations cannot be applied or rolled back.
+    Migration = 5,
+    /// Database client is not enabled in features of the installed crate.
+    MissedFeature = 6,
+}
+
 #[derive(Debug)]
 pub enum Error {
     RootNotFound,
+    ReadConfig(PathBuf, io::Error),
+    InvalidConfig(PathBuf, toml::de::Error),
     MissedEnvVar(String),
+    MissedFeature(&'static str),
+    DriftedMigrations(Vec<String>),
+    InvalidDateFormat(String),
+    InvalidEnvironment(String),
+    IncompleteClientCert,
 
+    Migra(migra::Error),
     Io(io::Error),
 }
 
@@ -18,10 +45,40 @@ impl fmt::Display for Error {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
             Error::RootNotFound => fmt.write_str("Cannot find root directory"),
+            Error::ReadConfig(ref path, ref error) => {
+                write!(fmt, "Cannot read {} - {error}", path.display())
+            }
+            Error::InvalidConfig(ref path, ref error) => {
+                write!(fmt, "Cannot parse {} - {error}", path.display())
+            }
             Error::MissedEnvVar(ref name) => {
-                write!(fmt, r#"Missed "{}" environment variable"#, name)
+                write!(fmt, r#"Missed "{name}" environment variable"#)
             }
-            Error::Io(ref error) => write!(fmt, "{}", error),
+            Error::MissedFeature(database_name) => write!(
+                fmt,
+                r#"You cannot use migra for "{database_name}".
+You need to reinstall crate with "{database_name}" feature.
+
+cargo install migra-cli --features {database_name}"#
+            ),
+            Error::DriftedMigrations(ref names) => write!(
+                fmt,
+                "Applied migrations were changed: {}. Restore them or use --allow-drift option",
+                names.join(", ")
+            ),
+            Error::InvalidDateFormat(ref date_format) => write!(
+                fmt,
+                r#"Date format "{date_format}" must produce a numeric migration version"#
+            ),
+            Error::InvalidEnvironment(ref environment) => write!(
+                fmt,
+                r#"Environment "{environment}" must be a name of a directory inside the seeds directory"#
+            ),
+            Error::IncompleteClientCert => fmt.write_str(
+                r#"Both "ssl_cert" and "ssl_key" database options are required to use a client certificate"#,
+            ),
+            Error::Migra(ref error) => write!(fmt, "{error}"),
+            Error::Io(ref error) => write!(fmt, "{error}"),
         }
     }
 }
@@ -40,3 +97,37 @@ impl From<io::Error> for Error {
         Error::Io(err)
     }
 }
+
+impl From<migra::Error> for Error {
+    #[inline]
+    fn from(err: migra::Error) -> Error {
+        Error::Migra(err)
+    }
+}
+
+impl Error {
+    /// Returns the exit code of the error category.
+    #[must_use]
+    pub fn exit_code(&self) -> ExitCode {
+        match *self {
+            Error::RootNotFound
+            | Error::ReadConfig(..)
+            | Error::InvalidConfig(..)
+            | Error::MissedEnvVar(_)
+            | Error::InvalidDateFormat(_)
+            | Error::IncompleteClientCert
+            | Error::Migra(
+                migra::Error::InvalidTableName(_) | migra::Error::UnresolvedPlaceholder { .. },
+            ) => ExitCode::Config,
+            Error::InvalidEnvironment(_) => ExitCode::Usage,
+            Error::MissedFeature(_) => ExitCode::MissedFeature,
+            Error::Migra(migra::Error::Db(ref error))
+                if matches!(error.kind(), DbKind::DatabaseConnection) =>
+            {
+                ExitCode::Connection
+            }
+            Error::Migra(migra::Error::Io(_)) | Error::Io(_) => ExitCode::Failure,
+            Error::DriftedMigrations(_) | Error::Migra(_) => ExitCode::Migration,
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use std::io::{self, IsTerminal};
+use std::str::FromStr;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LogFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!(r#"Unknown log format "{s}""#)),
+        }
+    }
+}
+
+/// Returns the level of logs: errors with `--quiet`, warnings by default, and more
+/// details for each `--verbose` flag.
+pub(crate) fn level(verbose: u8, quiet: bool) -> LevelFilter {
+    if quiet {
+        return LevelFilter::ERROR;
+    }
+
+    match verbose {
+        0 => LevelFilter::WARN,
+        1 => LevelFilter::INFO,
+        2 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    }
+}
+
+/// Writes logs to stderr, so they are never mixed with the output of commands.
+///
+/// Logs of dependencies are never more verbose than warnings.
+pub(crate) fn init(level: LevelFilter, format: LogFormat) {
+    let filter = Targets::new()
+        .with_target("migra", level)
+        .with_default(level.min(LevelFilter::WARN));
+
+    let registry = tracing_subscriber::registry().with(filter);
+    match format {
+        LogFormat::Text => registry
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_writer(io::stderr)
+                    .with_ansi(io::stderr().is_terminal())
+                    .without_time()
+                    .with_target(false),
+            )
+            .init(),
+        LogFormat::Json => registry
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .with_writer(io::stderr),
+            )
+            .init(),
+    }
+}
@@ -16,17 +16,38 @@ mod database;
 mod error;
 pub use error::Error;
 
+mod logging;
 mod opts;
+mod progress;
+mod report;
 
 use app::App;
 use config::Config;
+use error::ExitCode;
 use opts::{AppOpt, StructOpt};
+use std::process;
 
 fn main() {
     #[cfg(feature = "dotenv")]
     dotenv::dotenv().ok();
 
-    if let Err(err) = App::new(AppOpt::from_args()).run_command() {
-        panic!("Error: {}", err);
+    let app_opt = match AppOpt::from_args_safe() {
+        Ok(app_opt) => app_opt,
+        Err(err) if err.use_stderr() => {
+            eprintln!("{}", err.message);
+            process::exit(ExitCode::Usage as i32);
+        }
+        // Help and version are printed to stdout with a success exit code.
+        Err(err) => err.exit(),
+    };
+
+    logging::init(
+        logging::level(app_opt.verbose, app_opt.quiet),
+        app_opt.log_format,
+    );
+
+    if let Err(err) = App::new(app_opt).run_command() {
+        eprintln!("Error: {}", report::format_error(&err));
+        process::exit(err.exit_code() as i32);
     }
 }
@@ -1,3 +1,5 @@
+use crate::config::TransactionStrategy;
+use crate::logging::LogFormat;
 use std::path::PathBuf;
 use structopt::clap;
 pub use structopt::StructOpt;
@@ -8,6 +10,23 @@ pub(crate) struct AppOpt {
     #[structopt(name = "config", short, long)]
     pub config_path: Option<PathBuf>,
 
+    /// Logs more details: info with `-v`, debug with `-vv` and trace with `-vvv`.
+    #[structopt(short, long, parse(from_occurrences), global = true)]
+    pub verbose: u8,
+
+    /// Logs only errors.
+    #[structopt(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Format of logs that are written to stderr.
+    #[structopt(
+        long = "log-format",
+        global = true,
+        default_value = "text",
+        possible_values = &["text", "json"]
+    )]
+    pub log_format: LogFormat,
+
     #[structopt(subcommand)]
     pub command: Command,
 }
@@ -29,6 +48,10 @@ pub(crate) enum Command {
     #[structopt(name = "downgrade", visible_alias = "down")]
     Downgrade(DowngradeCommandOpt),
 
+    Seed(SeedCommandOpt),
+
+    History,
+
     Completions(CompletionsShell),
 }
 
@@ -43,6 +66,11 @@ pub(crate) struct MakeCommandOpt {
     /// Name of the migration to create in specify directory.
     #[structopt(parse(from_str))]
     pub migration_name: String,
+
+    /// Creates a single migration file with `-- migrate:up` and `-- migrate:down`
+    /// sections instead of a directory with `up.sql` and `down.sql` files.
+    #[structopt(long = "single-file")]
+    pub single_file: bool,
 }
 
 #[derive(Debug, StructOpt, Clone)]
@@ -55,6 +83,32 @@ pub(crate) struct UpgradeCommandOpt {
     /// How many existing migrations do we have to update.
     #[structopt(long = "number", short = "n")]
     pub migrations_number: Option<usize>,
+
+    /// Continues even if applied migrations were changed after they were applied.
+    #[structopt(long = "allow-drift")]
+    pub allow_drift: bool,
+
+    /// How many seconds to wait for other migration processes.
+    #[structopt(long = "lock-timeout")]
+    pub lock_timeout: Option<u64>,
+
+    /// How migrations are wrapped in transactions: in a single transaction, in a transaction
+    /// per migration or without transactions.
+    #[structopt(
+        long = "transaction-strategy",
+        possible_values = &["single", "per-migration", "none"]
+    )]
+    pub transaction_strategy: Option<TransactionStrategy>,
+
+    /// Applies all migrations in a single transaction. Shortcut for
+    /// `--transaction-strategy single`.
+    #[structopt(long = "single-transaction", conflicts_with = "transaction-strategy")]
+    pub single_transaction: bool,
+
+    /// Prints SQL that would be executed, including changes of the migrations table,
+    /// instead of executing it.
+    #[structopt(long = "dry-run")]
+    pub dry_run: bool,
 }
 
 #[derive(Debug, StructOpt, Clone)]
@@ -66,6 +120,44 @@ pub(crate) struct DowngradeCommandOpt {
     /// Rolls back all applied migrations. Ignores --number option.
     #[structopt(long = "all")]
     pub all_migrations: bool,
+
+    /// How many seconds to wait for other migration processes.
+    #[structopt(long = "lock-timeout")]
+    pub lock_timeout: Option<u64>,
+
+    /// How migrations are wrapped in transactions: in a single transaction, in a transaction
+    /// per migration or without transactions.
+    #[structopt(
+        long = "transaction-strategy",
+        possible_values = &["single", "per-migration", "none"]
+    )]
+    pub transaction_strategy: Option<TransactionStrategy>,
+
+    /// Rolls back all migrations in a single transaction. Shortcut for
+    /// `--transaction-strategy single`.
+    #[structopt(long = "single-transaction", conflicts_with = "transaction-strategy")]
+    pub single_transaction: bool,
+
+    /// Prints SQL that would be executed, including changes of the migrations table,
+    /// instead of executing it.
+    #[structopt(long = "dry-run")]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub(crate) struct SeedCommandOpt {
+    /// Also runs seeds from the subdirectory of the seeds directory with the name of
+    /// the environment, e.g. `dev`.
+    #[structopt(long = "env")]
+    pub environment: Option<String>,
+
+    /// Reruns seeds that have already run.
+    #[structopt(long = "force")]
+    pub force: bool,
+
+    /// How many seconds to wait for other seed processes.
+    #[structopt(long = "lock-timeout")]
+    pub lock_timeout: Option<u64>,
 }
 
 #[derive(Debug, StructOpt, Clone)]
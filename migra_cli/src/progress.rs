@@ -0,0 +1,35 @@
+use migra::migrator::{Direction, Event, ObserveMigrations};
+
+/// Prints progress of applying or rolling back migrations.
+///
+/// On a dry run the progress is printed to stderr, because only SQL is printed to stdout.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PrintProgress {
+    dry_run: bool,
+}
+
+impl PrintProgress {
+    pub(crate) fn new(dry_run: bool) -> Self {
+        PrintProgress { dry_run }
+    }
+
+    pub(crate) fn print(self, message: &str) {
+        if self.dry_run {
+            eprintln!("{message}");
+        } else {
+            println!("{message}");
+        }
+    }
+}
+
+impl ObserveMigrations for PrintProgress {
+    fn notify(&mut self, event: &Event<'_>) {
+        if let Event::MigrationStarted { direction, name } = *event {
+            let action = match direction {
+                Direction::Upgrade => "upgrade",
+                Direction::Downgrade => "downgrade",
+            };
+            self.print(&format!("{action} {name}..."));
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::error::Error;
+use migra::errors::{DbError, FailedStatement};
+use std::fmt::Write;
+
+/// How many lines are shown around the error position.
+const SNIPPET_CONTEXT_LINES: usize = 2;
+
+/// Formats the error with a snippet of the failed SQL statement and additional details
+/// that were reported by the database.
+pub(crate) fn format_error(err: &Error) -> String {
+    let mut report = err.to_string();
+
+    let db_error = match err {
+        Error::Migra(ref err) => find_db_error(err),
+        _ => None,
+    };
+    if let Some(db_error) = db_error {
+        let gutter = match db_error.statement() {
+            Some(statement) => {
+                report.push_str("\n\n");
+                write_snippet(&mut report, db_error.migration(), statement)
+            }
+            None => 1,
+        };
+
+        let details = [
+            ("SQLSTATE", db_error.code()),
+            ("detail", db_error.detail()),
+            ("hint", db_error.hint()),
+        ];
+        for (label, value) in details {
+            if let Some(value) = value {
+                write!(report, "\n{:gutter$} = {label}: {value}", "").unwrap();
+            }
+        }
+    }
+
+    report
+}
+
+fn find_db_error(err: &migra::Error) -> Option<&DbError> {
+    match err {
+        migra::Error::Db(ref db_error) => Some(db_error),
+        migra::Error::PartialRun(ref partial_run) => find_db_error(partial_run.origin()),
+        _ => None,
+    }
+}
+
+/// Writes lines of the failed statement around the error position and returns the width of
+/// line numbers.
+fn write_snippet(
+    report: &mut String,
+    migration: Option<&str>,
+    statement: &FailedStatement,
+) -> usize {
+    let lines = statement.sql().lines().collect::<Vec<_>>();
+
+    // Line and column of the error position inside the statement.
+    let error_location = statement.error_position().and_then(|pos| {
+        let before = statement
+            .sql()
+            .get(..pos.checked_sub(statement.span().start)?)?;
+        let line = before.matches('\n').count();
+        let column = before
+            .rsplit('\n')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .count();
+        Some((line, column))
+    });
+
+    let (first, last) = match error_location {
+        Some((line, _)) => (
+            line.saturating_sub(SNIPPET_CONTEXT_LINES),
+            line + SNIPPET_CONTEXT_LINES,
+        ),
+        None => (0, SNIPPET_CONTEXT_LINES * 2),
+    };
+    let last = last.min(lines.len().saturating_sub(1));
+
+    let gutter = (statement.line() + last).to_string().len();
+    write!(
+        report,
+        "{:gutter$}--> {}line {}\n{:gutter$} |",
+        "",
+        migration.map_or_else(String::new, |name| format!("{name}, ")),
+        statement.line() + error_location.map_or(0, |(line, _)| line),
+        ""
+    )
+    .unwrap();
+
+    if first > 0 {
+        write!(report, "\n{:gutter$} | ...", "").unwrap();
+    }
+    for (index, line) in lines.iter().enumerate().take(last + 1).skip(first) {
+        write!(report, "\n{:>gutter$} | {line}", statement.line() + index).unwrap();
+        if let Some((_, column)) = error_location.filter(|&(line, _)| line == index) {
+            write!(report, "\n{:gutter$} | {:column$}^", "", "").unwrap();
+        }
+    }
+    if last + 1 < lines.len() {
+        write!(report, "\n{:gutter$} | ...", "").unwrap();
+    }
+
+    write!(report, "\n{:gutter$} |", "").unwrap();
+    gutter
+}
@@ -0,0 +1,3 @@
+-- migrate:irreversible
+
+ALTER TABLE articles DROP COLUMN content;
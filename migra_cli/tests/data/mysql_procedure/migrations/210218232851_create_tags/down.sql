@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+DROP PROCEDURE add_tag;
+DROP TABLE tags;
@@ -0,0 +1,17 @@
+-- Your SQL goes here
+
+CREATE TABLE tags (
+    name        varchar(256)    NOT NULL UNIQUE
+);
+
+DELIMITER $$
+
+CREATE PROCEDURE add_tag(IN tag_name varchar(256))
+BEGIN
+    INSERT INTO tags (name) VALUES (tag_name);
+    SELECT COUNT(*) FROM tags;
+END$$
+
+DELIMITER ;
+
+CALL add_tag('migra');
@@ -0,0 +1,3 @@
+CREATE TABLE articles (
+    title       text        NOT NULL
+);
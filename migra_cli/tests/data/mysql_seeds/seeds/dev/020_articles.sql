@@ -0,0 +1 @@
+INSERT INTO articles (title) VALUES ('Draft');
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+
+CREATE TABLE tags (
+    name        text        NOT NULL
+);
+
+INSERT INTO tags (name)
+SELECT title
+FROM unknown_articles
+WHERE length(title) > 0;
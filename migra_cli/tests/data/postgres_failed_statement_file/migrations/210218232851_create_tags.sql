@@ -0,0 +1,14 @@
+-- Tags of articles
+
+-- migrate:up
+CREATE TABLE tags (
+    name        text        NOT NULL
+);
+
+INSERT INTO tags (name)
+SELECT title
+FROM unknown_articles
+WHERE length(title) > 0;
+
+-- migrate:down
+DROP TABLE tags;
@@ -0,0 +1,3 @@
+-- migrate:no-transaction
+
+DROP INDEX CONCURRENTLY articles_title_idx;
@@ -0,0 +1,3 @@
+-- migrate:no-transaction
+
+CREATE INDEX CONCURRENTLY articles_title_idx ON articles (title);
@@ -0,0 +1,4 @@
+CREATE TABLE ${table} (
+    title       text        NOT NULL DEFAULT '${title}',
+    author      text        NOT NULL DEFAULT '${author}'
+);
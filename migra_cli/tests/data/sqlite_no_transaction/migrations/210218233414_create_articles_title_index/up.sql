@@ -0,0 +1 @@
+CREATE INDEX articles_title_idx ON articles (title);
@@ -0,0 +1,3 @@
+-- migrate:no-transaction
+
+VACUUM;
@@ -0,0 +1,3 @@
+DELETE FROM articles WHERE title = 'Welcome';
+
+INSERT INTO articles (title) VALUES ('Welcome');
@@ -0,0 +1,31 @@
+-- migrate:up
+-- Your SQL goes here
+
+CREATE TABLE persons (
+    id              int             AUTO_INCREMENT PRIMARY KEY,
+    email           varchar(256)    NOT NULL UNIQUE,
+    display_name    text            NOT NULL,
+    created_at      timestamp       NOT NULL DEFAULT current_timestamp
+);
+
+ALTER TABLE articles
+    ADD COLUMN author_person_id int NULL
+        REFERENCES persons (id) ON UPDATE CASCADE ON DELETE CASCADE;
+
+-- migrate:down
+-- This section should undo anything in the upgrade section
+
+CREATE TABLE tmp_articles (
+    id          int         AUTO_INCREMENT PRIMARY KEY,
+    title       text        NOT NULL CHECK (length(title) > 0),
+    content     text        NOT NULL,
+    created_at  timestamp   NOT NULL DEFAULT current_timestamp
+);
+
+INSERT INTO tmp_articles (id, title, content, created_at)
+SELECT id, title, content, created_at FROM articles;
+
+DROP TABLE articles;
+ALTER TABLE tmp_articles RENAME TO articles;
+
+DROP TABLE persons;
@@ -0,0 +1,187 @@
+//! # Migra macros
+//!
+//! Procedural macros for [migra](https://crates.io/crates/migra). You don't need to add this
+//! crate as a dependency, just enable `embed` feature of the `migra` crate.
+//!
+#![deny(missing_debug_implementations)]
+#![deny(missing_docs)]
+#![deny(clippy::all, clippy::pedantic)]
+
+use migra_parser::{
+    has_directive, is_migration_dir, is_migration_file, parse_migration_file, parse_version,
+    section_line_offset, DOWN_SECTION_MARKER, IRREVERSIBLE_DIRECTIVE, UP_SECTION_MARKER,
+};
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use std::env;
+use std::fs;
+use std::path::Path;
+use syn::{parse_macro_input, LitStr};
+
+/// Embeds all migrations from the directory into the binary at compile time.
+///
+/// The path is relative to the `Cargo.toml` of the crate in which the macro is called.
+/// Each subdirectory with `up.sql` and `down.sql` files and each `.sql` file with
+/// `-- migrate:up` and `-- migrate:down` sections is a migration. Irreversible migrations
+/// with `-- migrate:irreversible` directive have no `down.sql` file or `-- migrate:down`
+/// section.
+///
+/// Expands to `migra::embedded::EmbeddedMigrations` sorted by migration version. Migrations
+/// without a version or with the same version cause a compile error.
+///
+/// **Note:** Cargo rebuilds the crate if the content of the embedded migrations is changed,
+/// but it doesn't track new migration directories. Touch the source file with the macro call
+/// to embed them.
+#[proc_macro]
+pub fn embed_migrations(input: TokenStream) -> TokenStream {
+    let dir = parse_macro_input!(input as LitStr);
+
+    expand_embed_migrations(&dir)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+fn expand_embed_migrations(dir: &LitStr) -> syn::Result<TokenStream2> {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR")
+        .map_err(|_| syn::Error::new(dir.span(), "CARGO_MANIFEST_DIR is not set"))?;
+    let dir_path = Path::new(&manifest_dir).join(dir.value());
+
+    let mut migration_paths = dir_path
+        .read_dir()
+        .map_err(|err| {
+            syn::Error::new(
+                dir.span(),
+                format!(
+                    "Cannot read migrations directory {}: {err}",
+                    dir_path.display()
+                ),
+            )
+        })?
+        .filter_map(|res| res.ok().map(|e| e.path()))
+        .filter(|path| is_migration_dir(path) || is_migration_file(path))
+        .map(|path| {
+            // Directory names may contain dots, e.g. `010_v1.2_fix`, so only files lose
+            // the extension.
+            let name = if is_migration_dir(&path) {
+                path.file_name()
+            } else {
+                path.file_stem()
+            };
+            let name = path_to_string(dir, Path::new(name.unwrap_or_default()))?;
+            let version = parse_version(&name).ok_or_else(|| {
+                syn::Error::new(
+                    dir.span(),
+                    format!(
+                        r#"Cannot parse version of "{name}" migration. Migration name must start with a timestamp or number followed by "_""#
+                    ),
+                )
+            })?;
+            Ok((version, name, path))
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    migration_paths.sort_by_key(|(version, _, _)| *version);
+
+    if let Some(duplicates) = migration_paths
+        .windows(2)
+        .find(|pair| pair[0].0 == pair[1].0)
+    {
+        return Err(syn::Error::new(
+            dir.span(),
+            format!(
+                "Migrations {} and {} have the same version {}",
+                duplicates[0].1, duplicates[1].1, duplicates[0].0
+            ),
+        ));
+    }
+
+    let migrations = migration_paths
+        .iter()
+        .map(|(_, name, path)| {
+            if is_migration_file(path) {
+                return expand_migration_file(dir, name, path);
+            }
+
+            let upgrade_sql_path = path_to_string(dir, &path.join("up.sql"))?;
+            if !path.join("down.sql").exists() {
+                let upgrade_sql = fs::read_to_string(path.join("up.sql")).map_err(|err| {
+                    syn::Error::new(
+                        dir.span(),
+                        format!("Cannot read migration file {upgrade_sql_path}: {err}"),
+                    )
+                })?;
+                if !has_directive(&upgrade_sql, IRREVERSIBLE_DIRECTIVE) {
+                    return Err(syn::Error::new(
+                        dir.span(),
+                        format!(
+                            "Migration directory {} must contain down.sql file or {IRREVERSIBLE_DIRECTIVE:?} directive",
+                            path.display()
+                        ),
+                    ));
+                }
+
+                return Ok(quote! {
+                    ::migra::embedded::EmbeddedMigration::new(
+                        #name,
+                        include_str!(#upgrade_sql_path),
+                        "",
+                    )
+                });
+            }
+
+            let downgrade_sql_path = path_to_string(dir, &path.join("down.sql"))?;
+
+            Ok(quote! {
+                ::migra::embedded::EmbeddedMigration::new(
+                    #name,
+                    include_str!(#upgrade_sql_path),
+                    include_str!(#downgrade_sql_path),
+                )
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        ::migra::embedded::EmbeddedMigrations::new(&[#(#migrations),*])
+    })
+}
+
+fn expand_migration_file(dir: &LitStr, name: &str, path: &Path) -> syn::Result<TokenStream2> {
+    let path_str = path_to_string(dir, path)?;
+    let content = fs::read_to_string(path).map_err(|err| {
+        syn::Error::new(
+            dir.span(),
+            format!("Cannot read migration file {}: {err}", path.display()),
+        )
+    })?;
+    let Some((upgrade_sql, downgrade_sql)) = parse_migration_file(&content) else {
+        return Err(syn::Error::new(
+            dir.span(),
+            format!(
+                "Migration file {} must contain {UP_SECTION_MARKER:?} and {DOWN_SECTION_MARKER:?} sections or {IRREVERSIBLE_DIRECTIVE:?} directive",
+                path.display()
+            ),
+        ));
+    };
+
+    let upgrade_line_offset = section_line_offset(&content, upgrade_sql);
+    let downgrade_line_offset = section_line_offset(&content, downgrade_sql);
+
+    // `include_str!` makes Cargo rebuild the crate when the migration file is changed.
+    Ok(quote! {
+        {
+            const _: &str = include_str!(#path_str);
+            ::migra::embedded::EmbeddedMigration::new(#name, #upgrade_sql, #downgrade_sql)
+                .with_line_offsets(#upgrade_line_offset, #downgrade_line_offset)
+        }
+    })
+}
+
+fn path_to_string(dir: &LitStr, path: &Path) -> syn::Result<String> {
+    path.to_str().map(String::from).ok_or_else(|| {
+        syn::Error::new(
+            dir.span(),
+            format!("Migration path {} is not valid UTF-8", path.display()),
+        )
+    })
+}
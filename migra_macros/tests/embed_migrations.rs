@@ -0,0 +1,78 @@
+use migra::embedded::EmbeddedMigrations;
+
+static MIGRATIONS: EmbeddedMigrations = migra::embed_migrations!("tests/migrations");
+
+#[test]
+fn embed_migrations_sorted_by_version() {
+    let names = MIGRATIONS
+        .migrations()
+        .iter()
+        .map(|migration| migration.name())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        names,
+        vec![
+            "9_create_tags",
+            "210218232851_create_articles",
+            "210218233414_create_persons",
+            "210218233512_create_comments",
+            "210218233620_drop_tags",
+            "210218233700_v1.2_rename_comments"
+        ]
+    );
+}
+
+#[test]
+fn embed_migration_sql() {
+    let migration = MIGRATIONS
+        .get("210218232851_create_articles")
+        .expect("Migration is not embedded");
+
+    assert_eq!(
+        migration.upgrade_sql(),
+        "CREATE TABLE articles (title text NOT NULL);\n"
+    );
+    assert_eq!(migration.downgrade_sql(), "DROP TABLE articles;\n");
+}
+
+#[test]
+fn embed_migration_directory_with_dots_in_name() {
+    let migration = MIGRATIONS
+        .get("210218233700_v1.2_rename_comments")
+        .expect("Migration is not embedded");
+
+    assert_eq!(
+        migration.upgrade_sql(),
+        "ALTER TABLE comments RENAME COLUMN content TO body;\n"
+    );
+}
+
+#[test]
+fn embed_single_file_migration_sql() {
+    let migration = MIGRATIONS
+        .get("210218233512_create_comments")
+        .expect("Migration is not embedded");
+
+    assert_eq!(
+        migration.upgrade_sql(),
+        "CREATE TABLE comments (content text NOT NULL);\n\n"
+    );
+    assert_eq!(migration.downgrade_sql(), "DROP TABLE comments;\n");
+    assert_eq!(migration.migration().upgrade_line_offset(), 1);
+    assert_eq!(migration.migration().downgrade_line_offset(), 4);
+}
+
+#[test]
+fn embed_irreversible_migration_sql() {
+    let migration = MIGRATIONS
+        .get("210218233620_drop_tags")
+        .expect("Migration is not embedded");
+
+    assert!(migration.migration().is_irreversible());
+    assert_eq!(
+        migration.upgrade_sql(),
+        "-- migrate:irreversible\nDROP TABLE tags;\n"
+    );
+    assert_eq!(migration.downgrade_sql(), "");
+}
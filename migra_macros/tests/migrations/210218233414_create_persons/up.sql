@@ -0,0 +1 @@
+CREATE TABLE persons (name text NOT NULL);
@@ -0,0 +1,5 @@
+-- migrate:up
+CREATE TABLE comments (content text NOT NULL);
+
+-- migrate:down
+DROP TABLE comments;
@@ -0,0 +1,255 @@
+//! # Migra parser
+//!
+//! Parsing of migration names, directories and single migration files, that is shared by
+//! [migra](https://crates.io/crates/migra) and the `embed_migrations!` macro of
+//! [migra-macros](https://crates.io/crates/migra-macros), so migrations are read from
+//! the filesystem and embedded into the binary in the same way. You don't need to add this
+//! crate as a dependency, all items are re-exported by the `migra` crate.
+//!
+#![deny(missing_debug_implementations)]
+#![deny(missing_docs)]
+#![deny(clippy::all, clippy::pedantic)]
+
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+
+/// Marker of the upgrade section in the single migration file.
+pub const UP_SECTION_MARKER: &str = "-- migrate:up";
+
+/// Marker of the downgrade section in the single migration file.
+pub const DOWN_SECTION_MARKER: &str = "-- migrate:down";
+
+/// Directive that marks the migration as irreversible. It's a separate line in the upgrade
+/// SQL content.
+///
+/// Irreversible migrations are applied as usual, but cannot be rolled back.
+pub const IRREVERSIBLE_DIRECTIVE: &str = "-- migrate:irreversible";
+
+/// Directive that makes the migration run outside a transaction. It's a separate line in the
+/// upgrade or downgrade SQL content and affects only this direction.
+///
+/// Some statements, like `CREATE INDEX CONCURRENTLY` in Postgres, cannot run inside
+/// a transaction. Postgres runs several statements of one query in an implicit transaction,
+/// so such migration should contain only one statement.
+pub const NO_TRANSACTION_DIRECTIVE: &str = "-- migrate:no-transaction";
+
+/// Checks if the SQL content contains the directive line, e.g. [`IRREVERSIBLE_DIRECTIVE`].
+///
+/// # Example
+///
+/// ```rust
+/// # use migra_parser::{has_directive, IRREVERSIBLE_DIRECTIVE};
+/// assert!(has_directive(
+///     "-- migrate:irreversible\nALTER TABLE persons DROP COLUMN email;\n",
+///     IRREVERSIBLE_DIRECTIVE
+/// ));
+/// assert!(!has_directive("ALTER TABLE persons DROP COLUMN email;\n", IRREVERSIBLE_DIRECTIVE));
+/// ```
+#[must_use]
+pub fn has_directive(content: &str, directive: &str) -> bool {
+    content.lines().any(|line| line.trim() == directive)
+}
+
+/// Splits content of the single migration file into upgrade and downgrade SQL content.
+///
+/// Each section starts with a marker line and lasts until the next marker or the end of
+/// the file. The content before the first marker is ignored. Returns `None` if the upgrade
+/// section is missed. The downgrade section is optional, because irreversible migrations
+/// don't have it.
+///
+/// # Example
+///
+/// ```rust
+/// # use migra_parser::parse_sections;
+/// let content = "-- migrate:up\nCREATE TABLE tags ();\n\n-- migrate:down\nDROP TABLE tags;\n";
+///
+/// assert_eq!(
+///     parse_sections(content),
+///     Some(("CREATE TABLE tags ();\n\n", Some("DROP TABLE tags;\n")))
+/// );
+/// ```
+#[must_use]
+pub fn parse_sections(content: &str) -> Option<(&str, Option<&str>)> {
+    let mut upgrade_sql = None;
+    let mut downgrade_sql = None;
+    let mut current_section: Option<(&mut Option<&str>, usize)> = None;
+    let mut offset = 0;
+
+    for line in content.split_inclusive('\n') {
+        let marker = line.trim();
+        if marker == UP_SECTION_MARKER || marker == DOWN_SECTION_MARKER {
+            if let Some((section, start)) = current_section.take() {
+                *section = Some(&content[start..offset]);
+            }
+            let section = if marker == UP_SECTION_MARKER {
+                &mut upgrade_sql
+            } else {
+                &mut downgrade_sql
+            };
+            current_section = Some((section, offset + line.len()));
+        }
+        offset += line.len();
+    }
+
+    if let Some((section, start)) = current_section {
+        *section = Some(&content[start..]);
+    }
+
+    upgrade_sql.map(|upgrade_sql| (upgrade_sql, downgrade_sql))
+}
+
+/// Splits content of the single migration file into upgrade and downgrade SQL content like
+/// [`parse_sections`], but also requires the downgrade section for migrations without
+/// [`IRREVERSIBLE_DIRECTIVE`]. The downgrade SQL content of irreversible migrations is empty.
+///
+/// # Example
+///
+/// ```rust
+/// # use migra_parser::parse_migration_file;
+/// assert_eq!(
+///     parse_migration_file("-- migrate:up\nSELECT 1;\n-- migrate:down\nSELECT 2;\n"),
+///     Some(("SELECT 1;\n", "SELECT 2;\n"))
+/// );
+/// assert_eq!(
+///     parse_migration_file("-- migrate:up\n-- migrate:irreversible\nSELECT 1;\n"),
+///     Some(("-- migrate:irreversible\nSELECT 1;\n", ""))
+/// );
+/// assert_eq!(parse_migration_file("-- migrate:up\nSELECT 1;\n"), None);
+/// ```
+#[must_use]
+pub fn parse_migration_file(content: &str) -> Option<(&str, &str)> {
+    parse_sections(content).and_then(|(upgrade_sql, downgrade_sql)| match downgrade_sql {
+        Some(downgrade_sql) => Some((upgrade_sql, downgrade_sql)),
+        None if has_directive(upgrade_sql, IRREVERSIBLE_DIRECTIVE) => Some((upgrade_sql, "")),
+        None => None,
+    })
+}
+
+/// Returns the number of lines before the section of the single migration file, that is
+/// a part of the content returned by [`parse_sections`] or [`parse_migration_file`].
+///
+/// Returns zero if the section isn't a part of the content.
+///
+/// # Example
+///
+/// ```rust
+/// # use migra_parser::{parse_migration_file, section_line_offset};
+/// let content = "-- migrate:up\nSELECT 1;\n\n-- migrate:down\nSELECT 2;\n";
+/// let (upgrade_sql, downgrade_sql) = parse_migration_file(content).unwrap();
+///
+/// assert_eq!(section_line_offset(content, upgrade_sql), 1);
+/// assert_eq!(section_line_offset(content, downgrade_sql), 4);
+/// ```
+#[must_use]
+pub fn section_line_offset(content: &str, section: &str) -> usize {
+    let start = (section.as_ptr() as usize).wrapping_sub(content.as_ptr() as usize);
+    content
+        .get(..start)
+        .map_or(0, |before| before.matches('\n').count())
+}
+
+/// Parses version from the numeric prefix of the migration name. The prefix is a timestamp
+/// or sequential number followed by `_` or the whole name.
+///
+/// # Example
+///
+/// ```rust
+/// # use migra_parser::parse_version;
+/// assert_eq!(parse_version("210218232851_create_articles"), Some(210218232851));
+/// assert_eq!(parse_version("10_add_index"), Some(10));
+/// assert_eq!(parse_version("2021-02-18_create_articles"), None);
+/// assert_eq!(parse_version("initial_migration"), None);
+/// ```
+#[must_use]
+pub fn parse_version(name: &str) -> Option<u64> {
+    let prefix = name.split_once('_').map_or(name, |(prefix, _)| prefix);
+    if prefix.is_empty() || !prefix.bytes().all(|b| b.is_ascii_digit()) {
+        None
+    } else {
+        prefix.parse().ok()
+    }
+}
+
+/// Checks if the directory is a migration.
+///
+/// Migration directory contains `up.sql` and `down.sql` files. Irreversible migrations
+/// contain only `up.sql` file with [`IRREVERSIBLE_DIRECTIVE`].
+///
+/// Any directory with `up.sql` file is a migration, so a missed `down.sql` file is reported
+/// when the migration is read instead of skipping the migration.
+#[must_use]
+pub fn is_migration_dir(path: &Path) -> bool {
+    path.join("up.sql").is_file()
+}
+
+/// Checks if the file is a single migration file.
+///
+/// Single migration file is a `.sql` file with [`UP_SECTION_MARKER`] and
+/// [`DOWN_SECTION_MARKER`] sections. Irreversible migrations have no downgrade section.
+/// See [`parse_sections`] for more information.
+///
+/// Other `.sql` files, e.g. a schema dump, may be stored next to migrations, so the file is
+/// a migration only if its name starts with a version (see [`parse_version`]) or it contains
+/// [`UP_SECTION_MARKER`].
+#[must_use]
+pub fn is_migration_file(path: &Path) -> bool {
+    is_sql_file(path)
+        && (path
+            .file_stem()
+            .and_then(OsStr::to_str)
+            .and_then(parse_version)
+            .is_some()
+            || fs::read_to_string(path)
+                .is_ok_and(|content| has_directive(&content, UP_SECTION_MARKER)))
+}
+
+/// Checks if the path is a `.sql` file. It isn't necessarily a migration, see
+/// [`is_migration_file`].
+#[must_use]
+pub fn is_sql_file(path: &Path) -> bool {
+    path.is_file() && path.extension().is_some_and(|extension| extension == "sql")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_migration_file_sections() {
+        assert_eq!(
+            parse_sections("-- header\n-- migrate:down\nSELECT 2;\n  -- migrate:up  \nSELECT 1;"),
+            Some(("SELECT 1;", Some("SELECT 2;\n")))
+        );
+        assert_eq!(
+            parse_sections("-- migrate:up\nSELECT 1;\n"),
+            Some(("SELECT 1;\n", None))
+        );
+        assert_eq!(parse_sections("-- migrate:down\nSELECT 2;\n"), None);
+        assert_eq!(parse_sections("SELECT 1;\n"), None);
+    }
+
+    #[test]
+    fn check_migration_files() -> std::io::Result<()> {
+        let dir_path = std::env::temp_dir().join(format!("migra_parser_{}", std::process::id()));
+        fs::create_dir_all(&dir_path)?;
+
+        let versioned_path = dir_path.join("210218232851_create_articles.sql");
+        fs::write(&versioned_path, "SELECT 1;\n")?;
+        let marked_path = dir_path.join("create_persons.sql");
+        fs::write(&marked_path, "-- migrate:up\nSELECT 1;\n")?;
+        let schema_path = dir_path.join("schema.sql");
+        fs::write(&schema_path, "CREATE TABLE articles ();\n")?;
+        let text_path = dir_path.join("210218232851_notes.txt");
+        fs::write(&text_path, "-- migrate:up\n")?;
+
+        assert!(is_migration_file(&versioned_path));
+        assert!(is_migration_file(&marked_path));
+        assert!(!is_migration_file(&schema_path));
+        assert!(is_sql_file(&schema_path));
+        assert!(!is_migration_file(&text_path));
+        assert!(!is_sql_file(&text_path));
+
+        fs::remove_dir_all(dir_path)
+    }
+}